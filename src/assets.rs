@@ -0,0 +1,61 @@
+use std::env;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/* The text files the game shows, built into the binary */
+pub const ASSETS: [(&str, &str); 5] = [
+    ("startrek.intro", include_str!("../assets/startrek.intro")),
+    ("startrek.doc", include_str!("../assets/startrek.doc")),
+    ("startrek.logo", include_str!("../assets/startrek.logo")),
+    ("startrek.fatal", include_str!("../assets/startrek.fatal")),
+    ("commands.txt", include_str!("../assets/commands.txt")),
+];
+
+static ASSETS_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/*
+ *	Where to look for replacements of the built in texts: the directory
+ *	given on the command line, else the assets directory of TREK_DIR if
+ *	there is one.
+ */
+pub fn find_assets_dir(dir: Option<&Path>) -> Option<PathBuf> {
+    match (dir, env::var("TREK_DIR")) {
+        (Some(dir), _) => Some(dir.to_path_buf()),
+        (None, Ok(trek_dir)) if !trek_dir.is_empty() => {
+            Some(Path::new(&trek_dir).join("assets")).filter(|dir| dir.is_dir())
+        }
+        _ => None,
+    }
+}
+
+/* Files in `dir` take the place of the built in ones from now on, it
+doesn't need to have all of them */
+pub fn set_assets_dir(dir: Option<&Path>) -> Result<(), String> {
+    if let Some(dir) = dir {
+        if !dir.is_dir() {
+            return Err(format!(
+                "The assets directory {} doesn't exist",
+                dir.display()
+            ));
+        }
+    }
+
+    let _ = ASSETS_DIR.set(dir.map(Path::to_path_buf));
+
+    Ok(())
+}
+
+/* The text of an asset, None if there is no such asset */
+pub fn load_asset(name: &str) -> Option<String> {
+    let dir = ASSETS_DIR.get_or_init(|| find_assets_dir(None));
+
+    if let Some(text) = dir.as_ref().and_then(|d| read_to_string(d.join(name)).ok()) {
+        return Some(text);
+    }
+
+    ASSETS
+        .iter()
+        .find(|(asset, _)| *asset == name)
+        .map(|(_, text)| text.to_string())
+}
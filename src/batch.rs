@@ -0,0 +1,115 @@
+use std::io::{BufRead, StdinLock, Stdout, Write};
+
+use crate::prelude::*;
+
+/*
+ *	Line oriented frontend for scripts and pipes: every answer is one line
+ *	of input and everything the game says is plain text. Prompts are
+ *	echoed together with the answer read so the output reads like a
+ *	transcript of the session.
+ */
+pub struct BatchFrontend<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    eof: bool,
+}
+
+impl BatchFrontend<StdinLock<'static>, Stdout> {
+    pub fn stdio() -> Self {
+        BatchFrontend::new(std::io::stdin().lock(), std::io::stdout())
+    }
+}
+
+impl<R: BufRead, W: Write> BatchFrontend<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        BatchFrontend {
+            input,
+            output,
+            eof: false,
+        }
+    }
+
+    /* Read the answer to a prompt, None once the input is exhausted */
+    fn read_answer(&mut self, message: &str) -> Option<String> {
+        let _ = write!(self.output, "{message} ");
+
+        let mut line = String::new();
+
+        if self.eof || matches!(self.input.read_line(&mut line), Ok(0) | Err(_)) {
+            self.eof = true;
+            let _ = writeln!(self.output);
+            return None;
+        }
+
+        let answer = line.trim().to_string();
+        let _ = writeln!(self.output, "{answer}");
+        let _ = self.output.flush();
+
+        Some(answer)
+    }
+
+    /* Keep asking until the answer will do, an empty line or the end
+    of the input cancels */
+    fn read_number<T>(&mut self, message: &str, min: T, max: T) -> Option<T>
+    where
+        T: std::str::FromStr + PartialOrd + std::fmt::Display + Copy,
+    {
+        loop {
+            match check_number(&self.read_answer(message)?, min, max) {
+                Ok(v) => return v,
+                Err(e) => self.println(&e),
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Frontend for BatchFrontend<R, W> {
+    fn print(&mut self, text: &str) {
+        let _ = write!(self.output, "{text}");
+    }
+
+    fn any_key(&mut self, message: &str) {
+        let _ = self.read_answer(message);
+    }
+
+    fn get_command(&mut self, message: &str) -> String {
+        match self.read_answer(message) {
+            Some(cmd) => cmd,
+            None => {
+                /* Nobody left at the helm */
+                self.println("End of input.");
+                String::from("xxx")
+            }
+        }
+    }
+
+    fn input_string(&mut self, message: &str) -> String {
+        self.read_answer(message).unwrap_or_default()
+    }
+
+    fn yesno(&mut self, message: &str, default: bool) -> bool {
+        let hint = if default { "(Y/n)" } else { "(y/N)" };
+
+        loop {
+            let answer = match self.read_answer(&format!("{message} {hint}")) {
+                None => return default,
+                Some(answer) => answer.to_lowercase(),
+            };
+
+            match answer.as_str() {
+                "" => return default,
+                "y" | "yes" => return true,
+                "n" | "no" => return false,
+                _ => self.println("Please type y or n"),
+            }
+        }
+    }
+
+    fn input_f32(&mut self, message: &str, min: f32, max: f32) -> Option<f32> {
+        self.read_number(message, min, max)
+    }
+
+    fn input_i32(&mut self, message: &str, min: i32, max: i32) -> Option<i32> {
+        self.read_number(message, min, max)
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::VecDeque;
+
+use crate::prelude::*;
+
+/*
+ *	Everything the captain can order, with the answers the engine would
+ *	otherwise prompt for
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Navigate { course: f32, warp: f32 },
+    ShortRangeScan,
+    LongRangeScan,
+    Phasers { energy: i32 },
+    Torpedo { course: f32 },
+    Shields { energy: i32 },
+    DamageControl { authorize_repair: bool },
+    Computer { option: i32 },
+    Save { filename: String },
+    Load { filename: String },
+    Resign,
+}
+
+impl Command {
+    /* The word typed at the Command? prompt */
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Command::Navigate { .. } => "nav",
+            Command::ShortRangeScan => "srs",
+            Command::LongRangeScan => "lrs",
+            Command::Phasers { .. } => "pha",
+            Command::Torpedo { .. } => "tor",
+            Command::Shields { .. } => "shi",
+            Command::DamageControl { .. } => "dam",
+            Command::Computer { .. } => "com",
+            Command::Save { .. } => "sav",
+            Command::Load { .. } => "loa",
+            Command::Resign => "xxx",
+        }
+    }
+
+    /* Answers to the prompts the command brings up, in order */
+    fn answers(&self) -> Vec<String> {
+        match self {
+            Command::Navigate { course, warp } => vec![course.to_string(), warp.to_string()],
+            Command::Phasers { energy } | Command::Shields { energy } => vec![energy.to_string()],
+            Command::Torpedo { course } => vec![course.to_string()],
+            Command::DamageControl { authorize_repair } => vec![authorize_repair.to_string()],
+            Command::Computer { option } => vec![option.to_string()],
+            Command::Save { filename } | Command::Load { filename } => vec![filename.clone()],
+            Command::ShortRangeScan | Command::LongRangeScan | Command::Resign => vec![],
+        }
+    }
+}
+
+/*
+ *	Frontend used by apply(): prompts are answered from a prepared list
+ *	(falling back to the prompt's default once it runs out) and whatever
+ *	the game says is collected as events
+ */
+#[derive(Debug, Default)]
+pub struct ScriptedFrontend {
+    answers: VecDeque<String>,
+    line: String,
+    events: Vec<Event>,
+}
+
+impl ScriptedFrontend {
+    pub fn new(answers: Vec<String>) -> Self {
+        ScriptedFrontend {
+            answers: answers.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn into_events(mut self) -> Vec<Event> {
+        if !self.line.is_empty() {
+            self.events.push(Event::Message(self.line));
+        }
+
+        self.events
+    }
+}
+
+impl Frontend for ScriptedFrontend {
+    fn print(&mut self, text: &str) {
+        self.line.push_str(text);
+    }
+
+    fn println(&mut self, text: &str) {
+        self.line.push_str(text);
+        self.events
+            .push(Event::Message(std::mem::take(&mut self.line)));
+    }
+
+    fn event(&mut self, event: Event) {
+        if !self.line.is_empty() {
+            self.events
+                .push(Event::Message(std::mem::take(&mut self.line)));
+        }
+
+        self.events.push(event);
+    }
+
+    fn any_key(&mut self, _message: &str) {}
+
+    fn get_command(&mut self, _message: &str) -> String {
+        self.answers.pop_front().unwrap_or_default()
+    }
+
+    fn input_string(&mut self, _message: &str) -> String {
+        self.answers.pop_front().unwrap_or_default()
+    }
+
+    fn yesno(&mut self, _message: &str, default: bool) -> bool {
+        self.answers
+            .pop_front()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default)
+    }
+
+    /* There is nobody to ask again, an answer that won't do cancels */
+    fn input_f32(&mut self, _message: &str, min: f32, max: f32) -> Option<f32> {
+        check_number(&self.answers.pop_front()?, min, max).ok()?
+    }
+
+    fn input_i32(&mut self, _message: &str, min: i32, max: i32) -> Option<i32> {
+        check_number(&self.answers.pop_front()?, min, max).ok()?
+    }
+}
+
+/* Build a new galaxy and brief the captain */
+pub fn start(seed: u64, level: DifficultyLevel, size: GalaxySize) -> (Game, Vec<Event>) {
+    let mut game = Game::initialize(seed, Difficulty::new(level), size);
+    let mut io = ScriptedFrontend::default();
+
+    begin_mission(&mut game, &mut io);
+
+    (game, io.into_events())
+}
+
+/* Carry out one command, the way run_game() would for a typed one */
+pub fn apply(game: &mut Game, command: Command) -> Vec<Event> {
+    let mut io = ScriptedFrontend::new(command.answers());
+
+    if !check_stranded(game, &mut io) {
+        execute_command(game, &mut io, command.keyword());
+    }
+
+    io.into_events()
+}
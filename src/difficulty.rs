@@ -0,0 +1,236 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DifficultyLevel {
+    Novice,
+    #[default]
+    Good,
+    Expert,
+    Emeritus,
+}
+
+pub const DIFFICULTY_LEVELS: [DifficultyLevel; 4] = [
+    DifficultyLevel::Novice,
+    DifficultyLevel::Good,
+    DifficultyLevel::Expert,
+    DifficultyLevel::Emeritus,
+];
+
+impl fmt::Display for DifficultyLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/* Case doesn't matter and any unique start of a name will do */
+impl FromStr for DifficultyLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+
+        let matches: Vec<DifficultyLevel> = DIFFICULTY_LEVELS
+            .into_iter()
+            .filter(|l| !s.is_empty() && l.to_string().to_lowercase().starts_with(&s))
+            .collect();
+
+        match matches[..] {
+            [level] => Ok(level),
+            _ => Err(format!(
+                "Unknown difficulty '{s}', expected novice, good, expert or emeritus"
+            )),
+        }
+    }
+}
+
+/*
+ *	How Klingons fight once the Enterprise is in their quadrant, see
+ *	tactics.rs. Energies are in percent of a fresh Klingon's.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KlingonTactics {
+    pub speed: u32,         /* Sectors a Klingon moves per turn */
+    pub range: f32,         /* Distance attacking Klingons close to */
+    pub retreat_below: u32, /* Energy under which a Klingon backs off */
+    pub cover: bool,        /* Retreat behind stars, out of torpedo reach */
+    pub flank: bool,        /* Come at the Enterprise from different sides */
+}
+
+/*
+ *	How the Klingon fleet moves between quadrants, see strategy.rs. Each
+ *	stardate every Klingon away from the Enterprise has a chance in 100
+ *	to move a quadrant. Several Klingons besiege a starbase faster.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct KlingonStrategy {
+    pub moves: u32,      /* Chance to move per stardate */
+    pub hunt_range: i32, /* Quadrants from which they go for the Enterprise */
+    pub siege_days: f32, /* For one Klingon to destroy a starbase */
+}
+
+/*
+ *	Everything a difficulty level changes. Good builds the galaxy with
+ *	the odds of the classic game, Commanders and the way Klingons move
+ *	are new at every level. The galaxy generation rolls a number from 1
+ *	to 100 per quadrant and compares it with the thresholds.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Difficulty {
+    pub level: DifficultyLevel,
+    pub klingon_thresholds: [u8; 3], /* Above these a quadrant gets 3, 2 or 1 Klingons */
+    pub starbase_threshold: u8,      /* Above this a quadrant gets a starbase */
+    pub min_days: i32,               /* Stardates to complete the mission ... */
+    pub extra_days: i32,             /* ... plus a random part of these */
+    pub klingon_energy: u32,
+    pub commanders: u32,       /* Klingons promoted to Commander */
+    pub super_commander: bool, /* One more to Super-Commander */
+    pub hit_base: u32,         /* Klingon hits are energy * (base + 1..spread) */
+    pub hit_spread: u32,       /* over distance, in thousandths */
+    pub tactics: KlingonTactics,
+    pub strategy: KlingonStrategy,
+}
+
+impl Difficulty {
+    pub fn new(level: DifficultyLevel) -> Difficulty {
+        match level {
+            DifficultyLevel::Novice => Difficulty {
+                level,
+                klingon_thresholds: [99, 97, 85],
+                starbase_threshold: 94,
+                min_days: 30,
+                extra_days: 10,
+                klingon_energy: 2000,
+                commanders: 1,
+                super_commander: false,
+                hit_base: 150,
+                hit_spread: 100,
+                tactics: KlingonTactics {
+                    speed: 1,
+                    range: 3.0,
+                    retreat_below: 0,
+                    cover: false,
+                    flank: false,
+                },
+                strategy: KlingonStrategy {
+                    moves: 10,
+                    hunt_range: 1,
+                    siege_days: 20.0,
+                },
+            },
+            DifficultyLevel::Good => Difficulty {
+                level,
+                klingon_thresholds: [98, 95, 80],
+                starbase_threshold: 96,
+                min_days: 25,
+                extra_days: 10,
+                klingon_energy: STARTING_ENERGY,
+                commanders: 2,
+                super_commander: false,
+                hit_base: 200,
+                hit_spread: 100,
+                tactics: KlingonTactics {
+                    speed: 2,
+                    range: 3.0,
+                    retreat_below: 10,
+                    cover: false,
+                    flank: false,
+                },
+                strategy: KlingonStrategy {
+                    moves: 20,
+                    hunt_range: 2,
+                    siege_days: 15.0,
+                },
+            },
+            DifficultyLevel::Expert => Difficulty {
+                level,
+                klingon_thresholds: [97, 93, 75],
+                starbase_threshold: 97,
+                min_days: 22,
+                extra_days: 10,
+                klingon_energy: 3500,
+                commanders: 3,
+                super_commander: true,
+                hit_base: 250,
+                hit_spread: 100,
+                tactics: KlingonTactics {
+                    speed: 3,
+                    range: 2.5,
+                    retreat_below: 15,
+                    cover: true,
+                    flank: true,
+                },
+                strategy: KlingonStrategy {
+                    moves: 30,
+                    hunt_range: 2,
+                    siege_days: 12.0,
+                },
+            },
+            DifficultyLevel::Emeritus => Difficulty {
+                level,
+                klingon_thresholds: [96, 90, 70],
+                starbase_threshold: 98,
+                min_days: 20,
+                extra_days: 8,
+                klingon_energy: 4000,
+                commanders: 4,
+                super_commander: true,
+                hit_base: 300,
+                hit_spread: 120,
+                tactics: KlingonTactics {
+                    speed: 4,
+                    range: 2.0,
+                    retreat_below: 20,
+                    cover: true,
+                    flank: true,
+                },
+                strategy: KlingonStrategy {
+                    moves: 40,
+                    hunt_range: 3,
+                    siege_days: 10.0,
+                },
+            },
+        }
+    }
+
+    /* Klingons to put in a quadrant for a roll of 1 to 100 */
+    pub fn klingons_for_roll(&self, r: u8) -> u32 {
+        let [three, two, one] = self.klingon_thresholds;
+
+        if r > three {
+            3
+        } else if r > two {
+            2
+        } else if r > one {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::new(DifficultyLevel::default())
+    }
+}
+
+/* Ask for the level of a new game, an empty answer takes Good */
+pub fn choose_difficulty(io: &mut dyn Frontend) -> DifficultyLevel {
+    loop {
+        let answer = io.input_string("Difficulty level (novice, good, expert, emeritus)?");
+
+        if answer.trim().is_empty() {
+            return DifficultyLevel::default();
+        }
+
+        match answer.parse() {
+            Ok(level) => return level,
+            Err(e) => io.println(&e),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/* What a transfer that couldn't be made was short of */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Insufficient {
+    pub needed: u32,
+    pub available: u32,
+}
+
+impl fmt::Display for Insufficient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} units needed, only {} available",
+            self.needed, self.available
+        )
+    }
+}
+
+/*
+ *	A store of energy: the ship's main power, its shields or a Klingon.
+ *	It never goes below zero, taking more than there is fails and leaves
+ *	it untouched. Saved as the plain number of units.
+ */
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Energy(u32);
+
+impl Energy {
+    pub const fn new(units: u32) -> Energy {
+        Energy(units)
+    }
+
+    pub fn units(&self) -> u32 {
+        self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn add(&mut self, units: u32) {
+        self.0 = self.0.saturating_add(units);
+    }
+
+    /* All or nothing */
+    pub fn take(&mut self, units: u32) -> Result<(), Insufficient> {
+        match self.0.checked_sub(units) {
+            Some(left) => {
+                self.0 = left;
+                Ok(())
+            }
+            None => Err(Insufficient {
+                needed: units,
+                available: self.0,
+            }),
+        }
+    }
+
+    /* As much as there is up to `units`, returns how much that was */
+    pub fn drain(&mut self, units: u32) -> u32 {
+        let taken = units.min(self.0);
+
+        self.0 -= taken;
+        taken
+    }
+
+    pub fn transfer_to(&mut self, to: &mut Energy, units: u32) -> Result<(), Insufficient> {
+        self.take(units)?;
+        to.add(units);
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Energy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn take_is_all_or_nothing() {
+        let mut energy = Energy::new(100);
+
+        assert_eq!(energy.take(40), Ok(()));
+        assert_eq!(energy.units(), 60);
+        assert_eq!(
+            energy.take(61),
+            Err(Insufficient {
+                needed: 61,
+                available: 60
+            })
+        );
+        assert_eq!(energy.units(), 60);
+        assert_eq!(energy.take(60), Ok(()));
+        assert!(energy.is_empty());
+    }
+
+    #[test]
+    fn drain_takes_what_there_is() {
+        let mut energy = Energy::new(50);
+
+        assert_eq!(energy.drain(20), 20);
+        assert_eq!(energy.drain(100), 30);
+        assert!(energy.is_empty());
+        assert_eq!(energy.drain(1), 0);
+    }
+
+    #[test]
+    fn add_saturates() {
+        let mut energy = Energy::new(u32::MAX - 1);
+
+        energy.add(10);
+        assert_eq!(energy.units(), u32::MAX);
+    }
+
+    #[test]
+    fn insufficient_transfer_moves_nothing() {
+        let mut from = Energy::new(100);
+        let mut to = Energy::new(5);
+
+        assert_eq!(
+            from.transfer_to(&mut to, 101),
+            Err(Insufficient {
+                needed: 101,
+                available: 100
+            })
+        );
+        assert_eq!((from.units(), to.units()), (100, 5));
+
+        assert_eq!(from.transfer_to(&mut to, 100), Ok(()));
+        assert_eq!((from.units(), to.units()), (0, 105));
+    }
+
+    #[test]
+    fn shields_take_from_and_give_back_to_the_ship() {
+        let mut ship = Game::initialize(1, Difficulty::default(), GalaxySize::default()).ship;
+        let total = ship.energy.units() + ship.shield.units();
+
+        assert_eq!(ship.set_shields(500), Ok(()));
+        assert_eq!(ship.shield.units(), 500);
+        assert_eq!(ship.energy.units() + ship.shield.units(), total);
+
+        assert_eq!(ship.set_shields(200), Ok(()));
+        assert_eq!(ship.shield.units(), 200);
+        assert_eq!(ship.energy.units() + ship.shield.units(), total);
+
+        assert!(ship.set_shields(total + 1).is_err());
+        assert_eq!(ship.shield.units(), 200);
+        assert_eq!(ship.energy.units() + ship.shield.units(), total);
+    }
+}
@@ -0,0 +1,360 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::prelude::*;
+
+const DCR_1: &str = "Damage Control report:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum LossReason {
+    ShipDestroyed,
+    TimeExpired,
+    Stranded,
+    Resigned,
+    RelievedOfCommand,
+    StarbasesLost,
+}
+
+/*
+ *	What happened while the engine carried out a command. Sector and
+ *	quadrant positions are the engine's own 0 based ones, the text
+ *	rendering adds one like the sensor displays do.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Event {
+    /* Narration from the ship's officers, as the console would show it */
+    Message(String),
+
+    QuadrantEntered {
+        quadrant: Vec2,
+        name: String,
+        mission_start: bool,
+    },
+    Docked,
+
+    KlingonHit {
+        sector: Vec2,
+        damage: u32,
+        remaining: u32,
+    },
+    KlingonUnharmed {
+        sector: Vec2,
+    },
+    KlingonDestroyed {
+        sector: Vec2,
+    },
+    KlingonArrived {
+        class: KlingonClass,
+        sector: Vec2,
+    },
+    KlingonEvaded {
+        class: KlingonClass,
+        sector: Vec2,
+        to: Vec2,
+    },
+    StarbaseDestroyed {
+        sector: Vec2,
+    },
+
+    /* Relayed by Uhura from starbases the Klingons besiege elsewhere */
+    StarbaseUnderAttack {
+        quadrant: Vec2,
+        falls: f32,
+    },
+    SiegeLifted {
+        quadrant: Vec2,
+    },
+    StarbaseLost {
+        quadrant: Vec2,
+    },
+
+    TorpedoTrack {
+        sector: Vec2,
+    },
+    TorpedoAbsorbed {
+        sector: Vec2,
+    },
+    TorpedoMissed,
+
+    ShipHit {
+        from: Vec2,
+        damage: u32,
+    },
+    ShieldsDown {
+        shields: u32,
+    },
+
+    DeviceDamaged {
+        device: DeviceType,
+        damage: u32,
+        by_hit: bool,
+    },
+    DeviceRepairImproved {
+        device: DeviceType,
+    },
+    DeviceRepaired {
+        device: DeviceType,
+    },
+
+    GameWon,
+    GameLost {
+        reason: LossReason,
+        stardate: f32,
+        klingons_left: u32,
+    },
+    FinalScore(Score),
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Message(text) => write!(f, "{text}"),
+
+            Event::QuadrantEntered {
+                name,
+                mission_start,
+                ..
+            } => {
+                if *mission_start {
+                    writeln!(
+                        f,
+                        "\nYour mission begins with your starship located
+in the galactic quadrant {name}"
+                    )
+                } else {
+                    writeln!(f, "Now entering {name} quadrant...")
+                }
+            }
+            Event::Docked => write!(f, "Shields dropped for docking purposes."),
+
+            Event::KlingonHit {
+                sector,
+                damage,
+                remaining,
+            } => {
+                write!(
+                    f,
+                    "{} unit hit on Klingon at sector {}, {}",
+                    damage,
+                    sector.x + 1,
+                    sector.y + 1
+                )?;
+
+                if *remaining > 0 {
+                    writeln!(f, "\n   (Sensors show {} units remaining.)", remaining)?;
+                }
+
+                Ok(())
+            }
+            Event::KlingonUnharmed { sector } => writeln!(
+                f,
+                "Sensors show no damage to enemy at {}, {}",
+                sector.x + 1,
+                sector.y + 1
+            ),
+            Event::KlingonDestroyed { .. } => write!(f, "*** Klingon Destroyed ***"),
+            Event::KlingonArrived { class, sector } => write!(
+                f,
+                "Science Officer Spock reports:
+  '{} entering the quadrant at sector {}, {}'",
+                class.name(),
+                sector.x + 1,
+                sector.y + 1
+            ),
+            Event::KlingonEvaded { class, sector, to } => write!(
+                f,
+                "*** {} at {}, {} evades the torpedo, now at {}, {} ***",
+                class.name(),
+                sector.x + 1,
+                sector.y + 1,
+                to.x + 1,
+                to.y + 1
+            ),
+            Event::StarbaseDestroyed { .. } => write!(f, "*** Starbase Destroyed ***"),
+
+            Event::StarbaseUnderAttack { quadrant, falls } => write!(
+                f,
+                "Lt. Uhura reports:
+  Subspace distress call from Starbase in quadrant {},{}:
+  'Under attack, estimated destruction stardate {:.2}'",
+                quadrant.x + 1,
+                quadrant.y + 1,
+                falls
+            ),
+            Event::SiegeLifted { quadrant } => write!(
+                f,
+                "Lt. Uhura reports:
+  Starbase in quadrant {},{} reports the siege is lifted.",
+                quadrant.x + 1,
+                quadrant.y + 1
+            ),
+            Event::StarbaseLost { quadrant } => write!(
+                f,
+                "Lt. Uhura reports:
+  Contact lost with Starbase in quadrant {},{}, it has been destroyed
+  by the Klingons.",
+                quadrant.x + 1,
+                quadrant.y + 1
+            ),
+
+            Event::TorpedoTrack { sector } => write!(f, "    {}, {}", sector.x + 1, sector.y + 1),
+            Event::TorpedoAbsorbed { sector } => writeln!(
+                f,
+                "Star at {}, {} absorbed torpedo energy.",
+                sector.x + 1,
+                sector.y + 1
+            ),
+            Event::TorpedoMissed => write!(f, "Torpedo Missed"),
+
+            Event::ShipHit { from, damage } => write!(
+                f,
+                "{} unit hit on Enterprise from sector {}, {}",
+                damage,
+                from.x + 1,
+                from.y + 1
+            ),
+            Event::ShieldsDown { shields } => {
+                writeln!(f, "    <Shields down to {} units>", shields)
+            }
+
+            Event::DeviceDamaged {
+                device,
+                by_hit: true,
+                ..
+            } => writeln!(
+                f,
+                "Damage Control reports\n   '{}' damaged by hit",
+                device.name()
+            ),
+            Event::DeviceDamaged { device, .. } => {
+                writeln!(f, "{DCR_1}    {} damaged", device.name())
+            }
+            Event::DeviceRepairImproved { device } => {
+                writeln!(f, "{DCR_1}    {} state of repair improved", device.name())
+            }
+            Event::DeviceRepaired { device } => {
+                writeln!(f, "{DCR_1}    {} repair completed", device.name())
+            }
+
+            Event::GameWon => write!(
+                f,
+                "Congratulations, Captain!  The last Klingon Battle Cruiser
+ menacing the Federation has been destroyed."
+            ),
+            Event::GameLost {
+                reason,
+                stardate,
+                klingons_left,
+            } => {
+                match reason {
+                    LossReason::ShipDestroyed => write!(
+                        f,
+                        "The Enterprise has been destroyed.
+    The Federation will be conquered.\n\n"
+                    )?,
+                    LossReason::RelievedOfCommand => writeln!(
+                        f,
+                        "That does it, Captain!!
+You are hereby relieved of command
+and sentenced to 99 stardates of hard
+labor on Cygnus 12!!"
+                    )?,
+                    LossReason::StarbasesLost => write!(
+                        f,
+                        "The last starbase has fallen to the Klingons.
+    Without its starbases the Federation cannot hold the galaxy.\n\n"
+                    )?,
+                    _ => {}
+                }
+
+                if matches!(
+                    reason,
+                    LossReason::ShipDestroyed
+                        | LossReason::TimeExpired
+                        | LossReason::Stranded
+                        | LossReason::StarbasesLost
+                ) {
+                    write!(f, "It is stardate {:.2}.\n\n", stardate)?;
+                }
+
+                writeln!(
+                    f,
+                    "There were {} Klingon Battlecruisers left at the
+ end of your mission.",
+                    klingons_left
+                )
+            }
+            Event::FinalScore(score) => write!(f, "{score}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lost(reason: LossReason) -> String {
+        Event::GameLost {
+            reason,
+            stardate: 2525.5,
+            klingons_left: 3,
+        }
+        .to_string()
+    }
+
+    #[test]
+    fn destroyed_ship_gives_the_stardate() {
+        assert_eq!(
+            lost(LossReason::ShipDestroyed),
+            "The Enterprise has been destroyed.
+    The Federation will be conquered.
+
+It is stardate 2525.50.
+
+There were 3 Klingon Battlecruisers left at the
+ end of your mission.\n"
+        );
+    }
+
+    #[test]
+    fn every_loss_tells_the_klingons_left() {
+        for reason in [
+            LossReason::ShipDestroyed,
+            LossReason::TimeExpired,
+            LossReason::Stranded,
+            LossReason::Resigned,
+            LossReason::RelievedOfCommand,
+            LossReason::StarbasesLost,
+        ] {
+            assert!(lost(reason).ends_with(
+                "There were 3 Klingon Battlecruisers left at the
+ end of your mission.\n"
+            ));
+        }
+
+        assert!(lost(LossReason::TimeExpired).starts_with("It is stardate 2525.50.\n\n"));
+        assert!(lost(LossReason::StarbasesLost).starts_with("The last starbase has fallen"));
+        assert!(lost(LossReason::RelievedOfCommand).starts_with("That does it, Captain!!\n"));
+        assert!(!lost(LossReason::Resigned).contains("stardate"));
+        assert!(!lost(LossReason::RelievedOfCommand).contains("It is stardate"));
+    }
+
+    #[test]
+    fn final_score_is_the_breakdown() {
+        let score = Score {
+            klingons_destroyed: 4,
+            starbases_lost: 0,
+            stardates_used: 8.0,
+            stardates_allowed: 25.0,
+            energy_spent: 500,
+            won: false,
+            survived: true,
+        };
+
+        assert_eq!(
+            Event::FinalScore(score.clone()).to_string(),
+            score.to_string()
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use crate::prelude::*;
+
+/*
+ *	Everything the game engine needs from the outside world.
+ *
+ *	The engine never touches the terminal directly: every message goes
+ *	through print/println and every answer comes back through one of the
+ *	input methods, so a Game can be driven by the console, a test, a bot
+ *	or any other user interface.
+ */
+pub trait Frontend {
+    /* Show some text, without adding a newline */
+    fn print(&mut self, text: &str);
+
+    fn println(&mut self, text: &str) {
+        self.print(text);
+        self.print("\n");
+    }
+
+    /* Report something that happened in the game. Text frontends just
+    print it, others can react to the event itself */
+    fn event(&mut self, event: Event) {
+        self.println(&event.to_string());
+    }
+
+    /* Decide the seed of a new game. Frontends that record or replay a
+    session hook in here, everyone else just takes the proposed one */
+    fn game_seed(&mut self, seed: u64) -> u64 {
+        seed
+    }
+
+    /* Decide the difficulty level of a new game like the seed, None has
+    the player choose one */
+    fn game_difficulty(&mut self, level: Option<DifficultyLevel>) -> Option<DifficultyLevel> {
+        level
+    }
+
+    /* Decide whether the session starts with the title and instructions */
+    fn show_intro(&mut self, show: bool) -> bool {
+        show
+    }
+
+    /* Decide the saved game a session carries on with, if any, like the
+    seed of a new one */
+    fn saved_game(&mut self, game: Option<Game>) -> Option<Game> {
+        game
+    }
+
+    /* Decide the dimensions of the galaxy of a new game, like the seed */
+    fn game_size(&mut self, size: GalaxySize) -> GalaxySize {
+        size
+    }
+
+    /* Called before every command so frontends that keep the game state
+    on screen can bring it up to date */
+    fn refresh(&mut self, _game: &Game) {}
+
+    /* True if the sensors and ship status are always on screen, the
+    engine then doesn't print them after every move */
+    fn has_status_display(&self) -> bool {
+        false
+    }
+
+    /* Called once a mission is over, None as the reason means it was won.
+    Frontends that keep a record of missions hook in here */
+    fn mission_over(&mut self, _game: &Game, _score: &Score, _reason: Option<LossReason>) {}
+
+    /* True while the answers come from a recorded session rather than
+    from the player */
+    fn replaying(&self) -> bool {
+        false
+    }
+
+    /* Wait for the player to acknowledge a message */
+    fn any_key(&mut self, message: &str);
+
+    /* Ask for the next command, returns an empty string on error */
+    fn get_command(&mut self, message: &str) -> String;
+
+    /* Ask for a free form line of text, such as a file name */
+    fn input_string(&mut self, message: &str) -> String;
+
+    fn yesno(&mut self, message: &str, default: bool) -> bool;
+
+    /* Input a value between min and max. Anything else is refused and
+    asked for again, None if the player cancels */
+    fn input_f32(&mut self, message: &str, min: f32, max: f32) -> Option<f32>;
+
+    /* Input an integer between min and max, like input_f32 */
+    fn input_i32(&mut self, message: &str, min: i32, max: i32) -> Option<i32>;
+}
+
+/* Lets a boxed frontend be wrapped by another one, like the journal */
+impl<F: Frontend + ?Sized> Frontend for Box<F> {
+    fn print(&mut self, text: &str) {
+        (**self).print(text)
+    }
+
+    fn println(&mut self, text: &str) {
+        (**self).println(text)
+    }
+
+    fn event(&mut self, event: Event) {
+        (**self).event(event)
+    }
+
+    fn game_seed(&mut self, seed: u64) -> u64 {
+        (**self).game_seed(seed)
+    }
+
+    fn game_difficulty(&mut self, level: Option<DifficultyLevel>) -> Option<DifficultyLevel> {
+        (**self).game_difficulty(level)
+    }
+
+    fn game_size(&mut self, size: GalaxySize) -> GalaxySize {
+        (**self).game_size(size)
+    }
+
+    fn show_intro(&mut self, show: bool) -> bool {
+        (**self).show_intro(show)
+    }
+
+    fn saved_game(&mut self, game: Option<Game>) -> Option<Game> {
+        (**self).saved_game(game)
+    }
+
+    fn refresh(&mut self, game: &Game) {
+        (**self).refresh(game)
+    }
+
+    fn has_status_display(&self) -> bool {
+        (**self).has_status_display()
+    }
+
+    fn mission_over(&mut self, game: &Game, score: &Score, reason: Option<LossReason>) {
+        (**self).mission_over(game, score, reason)
+    }
+
+    fn replaying(&self) -> bool {
+        (**self).replaying()
+    }
+
+    fn any_key(&mut self, message: &str) {
+        (**self).any_key(message)
+    }
+
+    fn get_command(&mut self, message: &str) -> String {
+        (**self).get_command(message)
+    }
+
+    fn input_string(&mut self, message: &str) -> String {
+        (**self).input_string(message)
+    }
+
+    fn yesno(&mut self, message: &str, default: bool) -> bool {
+        (**self).yesno(message, default)
+    }
+
+    fn input_f32(&mut self, message: &str, min: f32, max: f32) -> Option<f32> {
+        (**self).input_f32(message, min, max)
+    }
+
+    fn input_i32(&mut self, message: &str, min: i32, max: i32) -> Option<i32> {
+        (**self).input_i32(message, min, max)
+    }
+}
+
+/*
+ *	Check a number typed by the player: Ok(None) for an empty answer,
+ *	which cancels, Err with what to tell the player if it won't do.
+ */
+pub fn check_number<T>(answer: &str, min: T, max: T) -> Result<Option<T>, String>
+where
+    T: std::str::FromStr + PartialOrd + std::fmt::Display,
+{
+    let answer = answer.trim();
+
+    if answer.is_empty() {
+        return Ok(None);
+    }
+
+    match answer.parse::<T>() {
+        Ok(v) if v >= min && v <= max => Ok(Some(v)),
+        Ok(_) => Err(format!("Please type a number from {min} to {max}")),
+        Err(_) => Err("Please type a number, or nothing to cancel".to_string()),
+    }
+}
+
+/*
+ *	Ask for a number until `check` accepts it, check returns why it
+ *	refuses one. None if the player cancels.
+ */
+pub fn ask_f32(
+    io: &mut dyn Frontend,
+    message: &str,
+    min: f32,
+    max: f32,
+    check: impl Fn(f32) -> Result<(), String>,
+) -> Option<f32> {
+    loop {
+        let v = io.input_f32(message, min, max)?;
+
+        match check(v) {
+            Ok(()) => return Some(v),
+            Err(reason) => io.println(&reason),
+        }
+    }
+}
+
+pub fn ask_i32(
+    io: &mut dyn Frontend,
+    message: &str,
+    min: i32,
+    max: i32,
+    check: impl Fn(i32) -> Result<(), String>,
+) -> Option<i32> {
+    loop {
+        let v = io.input_i32(message, min, max)?;
+
+        match check(v) {
+            Ok(()) => return Some(v),
+            Err(reason) => io.println(&reason),
+        }
+    }
+}
@@ -0,0 +1,444 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+pub const SCORES_FILE: &str = "startrek.scores";
+
+/* How many places the ranking shows */
+const RANKING_SIZE: usize = 10;
+
+/* One finished mission in the high score table */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreEntry {
+    pub captain: String,
+    pub score: i32,
+    pub outcome: String,
+    #[serde(default)]
+    pub difficulty: Option<String>,
+    pub seed: u64,
+    pub date: String, /* YYYY-MM-DD */
+    pub klingons_destroyed: u32,
+}
+
+/* What a captain has achieved over all the missions in the table */
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Career {
+    pub missions: u32,
+    pub won: u32,
+    pub best: i32,
+    pub total: i32,
+    pub klingons_destroyed: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    pub entries: Vec<ScoreEntry>,
+}
+
+/*
+ *	Where the scores and the command history live: the game directory
+ *	when TREK_DIR is set, otherwise the XDG data directory of the user.
+ */
+pub fn data_dir() -> PathBuf {
+    if let Ok(dir) = env::var("TREK_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    let data_dir = match env::var("XDG_DATA_HOME") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => Path::new(&env::var("HOME").unwrap_or_default()).join(".local/share"),
+    };
+
+    data_dir.join("startrek")
+}
+
+pub fn scores_path() -> PathBuf {
+    data_dir().join(SCORES_FILE)
+}
+
+impl HighScores {
+    /* A missing file is just an empty table */
+    pub fn load(path: &Path) -> std::io::Result<HighScores> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HighScores::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /* Best score first, older entries win ties */
+    pub fn ranking(&self) -> Vec<&ScoreEntry> {
+        let mut ranking: Vec<&ScoreEntry> = self.entries.iter().collect();
+
+        ranking.sort_by_key(|e| std::cmp::Reverse(e.score));
+        ranking
+    }
+
+    /* Adds an entry, returns its place in the ranking counting from 1 */
+    pub fn add(&mut self, entry: ScoreEntry) -> usize {
+        let place = 1 + self
+            .entries
+            .iter()
+            .filter(|e| e.score >= entry.score)
+            .count();
+
+        self.entries.push(entry);
+        place
+    }
+
+    pub fn career(&self, captain: &str) -> Career {
+        let mut career = Career::default();
+
+        for e in self.entries.iter().filter(|e| e.captain == captain) {
+            if career.missions == 0 || e.score > career.best {
+                career.best = e.score;
+            }
+
+            career.missions += 1;
+            career.total += e.score;
+            career.klingons_destroyed += e.klingons_destroyed;
+
+            if e.outcome == outcome_name(None) {
+                career.won += 1;
+            }
+        }
+
+        career
+    }
+
+    pub fn captains(&self) -> Vec<&str> {
+        let mut captains: Vec<&str> = Vec::new();
+
+        for e in &self.entries {
+            if !captains.contains(&e.captain.as_str()) {
+                captains.push(&e.captain);
+            }
+        }
+
+        captains
+    }
+}
+
+/* None stands for a mission that was won */
+pub fn outcome_name(reason: Option<LossReason>) -> &'static str {
+    match reason {
+        None => "Won",
+        Some(LossReason::ShipDestroyed) => "Destroyed",
+        Some(LossReason::TimeExpired) => "Out of time",
+        Some(LossReason::Stranded) => "Stranded",
+        Some(LossReason::Resigned) => "Resigned",
+        Some(LossReason::RelievedOfCommand) => "Relieved",
+        Some(LossReason::StarbasesLost) => "Bases lost",
+    }
+}
+
+/* Today's date as YYYY-MM-DD, in UTC */
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86400)
+        .unwrap_or(0) as i64;
+
+    /* Civil date from days since 1970-01-01, after Howard Hinnant */
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+pub fn show_ranking(io: &mut dyn Frontend, scores: &HighScores, highlight: Option<usize>) {
+    let ranking = scores.ranking();
+
+    if ranking.is_empty() {
+        io.println("No missions on record yet.");
+        return;
+    }
+
+    io.println("\n                  Starfleet Hall of Fame\n");
+    io.println("Rank Captain         Score Outcome     Level    Date       Seed");
+
+    for (i, e) in ranking.iter().take(RANKING_SIZE).enumerate() {
+        let marker = if highlight == Some(i + 1) { ">" } else { " " };
+        let captain: String = e.captain.chars().take(14).collect();
+
+        io.println(&format!(
+            "{marker}{:>3} {:<14} {:>6} {:<11} {:<8} {} {}",
+            i + 1,
+            captain,
+            e.score,
+            e.outcome,
+            e.difficulty.as_deref().unwrap_or("-"),
+            e.date,
+            e.seed
+        ));
+    }
+}
+
+pub fn show_career(io: &mut dyn Frontend, scores: &HighScores, captain: &str) {
+    let career = scores.career(captain);
+
+    io.println(&format!(
+        "Captain {captain}: {} mission{}, {} won, {} Klingons destroyed, best score {}, average {}",
+        career.missions,
+        if career.missions == 1 { "" } else { "s" },
+        career.won,
+        career.klingons_destroyed,
+        career.best,
+        career.total / career.missions.max(1) as i32
+    ));
+}
+
+/* The --scores view */
+pub fn show_scores(io: &mut dyn Frontend) {
+    let path = scores_path();
+
+    match HighScores::load(&path) {
+        Ok(scores) => {
+            show_ranking(io, &scores, None);
+
+            let captains = scores.captains();
+
+            if !captains.is_empty() {
+                io.println("");
+            }
+
+            for captain in captains {
+                show_career(io, &scores, captain);
+            }
+        }
+        Err(e) => io.println(&format!("Unable to read {}: {e}", path.display())),
+    }
+}
+
+/*
+ *	Puts a finished mission in the high score table at `path` and shows
+ *	where it ranks. Nothing is written if the captain gives no name, or
+ *	while a recorded session is played back: its missions are already
+ *	in the table.
+ */
+pub fn record_score(
+    io: &mut dyn Frontend,
+    path: &Path,
+    game: &Game,
+    score: &Score,
+    reason: Option<LossReason>,
+) {
+    let captain = io.input_string("Enter your name for the Starfleet records:");
+    let captain = captain.trim();
+
+    if captain.is_empty() {
+        return;
+    }
+
+    let mut scores = match HighScores::load(path) {
+        Ok(scores) => scores,
+        Err(e) => {
+            /* Don't overwrite a table we couldn't read */
+            io.println(&format!("Unable to read {}: {e}", path.display()));
+            return;
+        }
+    };
+
+    let place = scores.add(ScoreEntry {
+        captain: captain.to_string(),
+        score: score.total(),
+        outcome: outcome_name(reason).to_string(),
+        difficulty: Some(game.difficulty.level.to_string()),
+        seed: game.seed,
+        date: today(),
+        klingons_destroyed: score.klingons_destroyed,
+    });
+
+    if !io.replaying() {
+        if let Err(e) = scores.save(path) {
+            io.println(&format!("Unable to write {}: {e}", path.display()));
+        }
+    }
+
+    show_ranking(io, &scores, Some(place));
+
+    if place > RANKING_SIZE {
+        io.println(&format!("This mission ranks number {place}."));
+    }
+
+    show_career(io, &scores, captain);
+    io.println("");
+}
+
+/*
+ *	Keeps the high score table for the frontend it wraps, the engine
+ *	itself never touches it: every mission that ends is recorded there.
+ */
+pub struct ScoreKeeper<F: Frontend> {
+    inner: F,
+    path: PathBuf,
+}
+
+impl<F: Frontend> ScoreKeeper<F> {
+    pub fn new(inner: F, path: PathBuf) -> Self {
+        ScoreKeeper { inner, path }
+    }
+}
+
+impl<F: Frontend> Frontend for ScoreKeeper<F> {
+    fn print(&mut self, text: &str) {
+        self.inner.print(text);
+    }
+
+    fn println(&mut self, text: &str) {
+        self.inner.println(text);
+    }
+
+    fn event(&mut self, event: Event) {
+        self.inner.event(event);
+    }
+
+    fn game_seed(&mut self, seed: u64) -> u64 {
+        self.inner.game_seed(seed)
+    }
+
+    fn game_difficulty(&mut self, level: Option<DifficultyLevel>) -> Option<DifficultyLevel> {
+        self.inner.game_difficulty(level)
+    }
+
+    fn game_size(&mut self, size: GalaxySize) -> GalaxySize {
+        self.inner.game_size(size)
+    }
+
+    fn show_intro(&mut self, show: bool) -> bool {
+        self.inner.show_intro(show)
+    }
+
+    fn saved_game(&mut self, game: Option<Game>) -> Option<Game> {
+        self.inner.saved_game(game)
+    }
+
+    fn refresh(&mut self, game: &Game) {
+        self.inner.refresh(game);
+    }
+
+    fn has_status_display(&self) -> bool {
+        self.inner.has_status_display()
+    }
+
+    fn mission_over(&mut self, game: &Game, score: &Score, reason: Option<LossReason>) {
+        let path = self.path.clone();
+
+        record_score(self, &path, game, score, reason);
+    }
+
+    fn replaying(&self) -> bool {
+        self.inner.replaying()
+    }
+
+    fn any_key(&mut self, message: &str) {
+        self.inner.any_key(message);
+    }
+
+    fn get_command(&mut self, message: &str) -> String {
+        self.inner.get_command(message)
+    }
+
+    fn input_string(&mut self, message: &str) -> String {
+        self.inner.input_string(message)
+    }
+
+    fn yesno(&mut self, message: &str, default: bool) -> bool {
+        self.inner.yesno(message, default)
+    }
+
+    fn input_f32(&mut self, message: &str, min: f32, max: f32) -> Option<f32> {
+        self.inner.input_f32(message, min, max)
+    }
+
+    fn input_i32(&mut self, message: &str, min: i32, max: i32) -> Option<i32> {
+        self.inner.input_i32(message, min, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("startrek-{}-{name}", std::process::id()))
+    }
+
+    fn batch(input: &str) -> BatchFrontend<&[u8], Vec<u8>> {
+        BatchFrontend::new(input.as_bytes(), Vec::new())
+    }
+
+    #[test]
+    fn replayed_missions_are_not_recorded_again() {
+        let scores = temp_path("replay.scores");
+        let journal = temp_path("replay.journal");
+        let journal = journal.to_str().unwrap();
+        let size = GalaxySize::default();
+
+        /* Resign at once, sign the records and don't volunteer again */
+        let mut io = ScoreKeeper::new(
+            JournalFrontend::create(journal, batch("\nxxx\nKirk\nn\n")).unwrap(),
+            scores.clone(),
+        );
+        run_game(&mut io, 7, Some(DifficultyLevel::Novice), size);
+
+        let recorded = fs::read_to_string(&scores).unwrap();
+        assert_eq!(HighScores::load(&scores).unwrap().entries.len(), 1);
+
+        let mut io = ScoreKeeper::new(
+            ReplayFrontend::open(journal, false, batch("")).unwrap(),
+            scores.clone(),
+        );
+        run_game(&mut io, 8, None, size);
+
+        assert_eq!(fs::read_to_string(&scores).unwrap(), recorded);
+
+        fs::remove_file(&scores).unwrap();
+        fs::remove_file(journal).unwrap();
+    }
+
+    #[test]
+    fn ranking_puts_the_best_first() {
+        let mut scores = HighScores::default();
+        let entry = |captain: &str, score| ScoreEntry {
+            captain: captain.to_string(),
+            score,
+            outcome: outcome_name(None).to_string(),
+            difficulty: None,
+            seed: 1,
+            date: "2280-01-01".to_string(),
+            klingons_destroyed: 2,
+        };
+
+        assert_eq!(scores.add(entry("Kirk", 100)), 1);
+        assert_eq!(scores.add(entry("Sulu", 300)), 1);
+        assert_eq!(scores.add(entry("Kirk", 100)), 3);
+
+        let ranking: Vec<i32> = scores.ranking().iter().map(|e| e.score).collect();
+        assert_eq!(ranking, [300, 100, 100]);
+
+        let career = scores.career("Kirk");
+        assert_eq!((career.missions, career.best, career.total), (2, 100, 200));
+        assert_eq!(career.klingons_destroyed, 4);
+    }
+}
@@ -1,81 +1,168 @@
+use std::fs;
 use std::io::Write;
+use std::path::PathBuf;
 
 use inquire::*;
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
 
-pub fn any_key(message: &str) {
-    let _ = Text::new(message).prompt_skippable();
+use crate::prelude::*;
+
+pub const HISTORY_FILE: &str = "startrek.history";
+
+/* How many commands are remembered between sessions */
+pub const HISTORY_SIZE: usize = 1000;
+
+pub fn history_path() -> PathBuf {
+    data_dir().join(HISTORY_FILE)
 }
 
-pub fn get_command(message: &str) -> String {
-    let answer = Text::new(message).prompt();
+/* Tab completion at the Command? prompt */
+struct CommandHelper;
 
-    match answer {
-        Ok(cmd) => {
-            if cmd.len() > 3 {
-                String::from(&cmd[..3])
-            } else {
-                cmd
-            }
-        }
+impl Completer for CommandHelper {
+    type Candidate = Pair;
 
-        Err(_) => String::from(""),
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (start, candidates) = complete_command(line, pos);
+
+        Ok((
+            start,
+            candidates
+                .into_iter()
+                .map(|(replacement, display)| Pair {
+                    display,
+                    replacement,
+                })
+                .collect(),
+        ))
     }
 }
 
-pub fn yesno(message: &str, default: bool) -> bool {
-    let answer = Confirm::new(message)
-        .with_default(default)
-        //.with_help_message("This data is stored for good reasons")
-        .prompt();
+impl Hinter for CommandHelper {
+    type Hint = String;
+}
+
+impl Highlighter for CommandHelper {}
+
+impl Validator for CommandHelper {}
+
+impl Helper for CommandHelper {}
+
+/*
+ *	The interactive terminal frontend, built on top of inquire prompts.
+ *	Commands are read with line editing, tab completion and a history
+ *	kept across sessions.
+ */
+pub struct ConsoleFrontend {
+    editor: Option<Editor<CommandHelper, FileHistory>>,
+}
+
+impl ConsoleFrontend {
+    pub fn new() -> Self {
+        let config = Config::builder().max_history_size(HISTORY_SIZE).map(|c| {
+            c.auto_add_history(false)
+                .completion_type(CompletionType::List)
+                .build()
+        });
+
+        let editor = config
+            .ok()
+            .and_then(|config| Editor::with_config(config).ok())
+            .map(|mut editor: Editor<CommandHelper, FileHistory>| {
+                editor.set_helper(Some(CommandHelper));
+
+                /* No history yet is fine */
+                let _ = editor.load_history(&history_path());
 
-    match answer {
-        Ok(v) => v,
-        Err(_) => default,
+                editor
+            });
+
+        ConsoleFrontend { editor }
     }
 }
 
-/* Input a value between 0.00 and 9.99 */
-pub fn input_f32(message: &str, /*help_message: &str, */ min: f32, max: f32) -> f32 {
-    let amount = CustomType::<f32>::new(message)
-        .with_formatter(&|i| format!("{:.2}", i))
-        .with_error_message("Please type a valid number")
-        //.with_help_message(help_message)
-        .prompt();
-
-    match amount {
-        Ok(v) => {
-            if v < min {
-                min
-            } else if v > max {
-                max
-            } else {
-                v
-            }
+impl Default for ConsoleFrontend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/* Esc, Ctrl-C or an empty answer cancel */
+fn read_number<T>(message: &str, min: T, max: T) -> Option<T>
+where
+    T: std::str::FromStr + PartialOrd + std::fmt::Display + Copy,
+{
+    loop {
+        let answer = Text::new(message)
+            .with_help_message("Esc or an empty answer cancels")
+            .prompt()
+            .ok()?;
+
+        match check_number(&answer, min, max) {
+            Ok(v) => return v,
+            Err(e) => println!("{e}"),
         }
-        Err(_) => min,
     }
 }
 
-/* Integer: unsigned, or returns -1 for blank/error */
-pub fn input_i32(message: &str, /*help_message: &str,*/ min: i32, max: i32) -> i32 {
-    //input(8).trim().parse().unwrap_or(-1)
-
-    let amount = CustomType::<i32>::new(message)
-        .with_formatter(&|i| format!("{:.2}", i))
-        .with_error_message("Please type a valid number")
-        //.with_help_message(help_message)
-        .prompt();
-
-    match amount {
-        Ok(v) => {
-            if v < min {
-                min
-            } else if v > max {
-                max
-            } else {
-                v
+impl Frontend for ConsoleFrontend {
+    fn print(&mut self, text: &str) {
+        print!("{text}");
+        let _ = std::io::stdout().flush();
+    }
+
+    fn any_key(&mut self, message: &str) {
+        let _ = Text::new(message).prompt_skippable();
+    }
+
+    fn get_command(&mut self, message: &str) -> String {
+        let Some(editor) = self.editor.as_mut() else {
+            return Text::new(message).prompt().unwrap_or_default();
+        };
+
+        let line = editor.readline(&format!("{message} ")).unwrap_or_default();
+
+        if !line.trim().is_empty() && editor.add_history_entry(line.trim()).unwrap_or(false) {
+            let path = history_path();
+
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
             }
+
+            let _ = editor.append_history(&path);
         }
-        Err(_) => min,
+
+        line
+    }
+
+    fn input_string(&mut self, message: &str) -> String {
+        Text::new(message).prompt().unwrap_or_default()
+    }
+
+    fn yesno(&mut self, message: &str, default: bool) -> bool {
+        let answer = Confirm::new(message)
+            .with_default(default)
+            //.with_help_message("This data is stored for good reasons")
+            .prompt();
+
+        answer.unwrap_or(default)
+    }
+
+    fn input_f32(&mut self, message: &str, min: f32, max: f32) -> Option<f32> {
+        read_number(message, min, max)
+    }
+
+    fn input_i32(&mut self, message: &str, min: i32, max: i32) -> Option<i32> {
+        read_number(message, min, max)
     }
 }
@@ -0,0 +1,481 @@
+use std::fs::{read_to_string, rename, File};
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use crate::prelude::*;
+
+pub const DEFAULT_JOURNAL_FILE: &str = "startrek.journal";
+
+const JOURNAL_HEADER: &str = "# startrek journal 1";
+
+/* A galaxy size as "<width>x<height> <quadrant width>x<quadrant height>" */
+fn format_size(size: &GalaxySize) -> String {
+    format!(
+        "{}x{} {}x{}",
+        size.width, size.height, size.quadrant_width, size.quadrant_height
+    )
+}
+
+fn parse_size(value: &str) -> Option<GalaxySize> {
+    let dimensions = value
+        .split([' ', 'x'])
+        .map(|n| n.parse().ok())
+        .collect::<Option<Vec<u32>>>()?;
+
+    match dimensions[..] {
+        [w, h, qw, qh] => GalaxySize::new(w, h, qw, qh).ok(),
+        _ => None,
+    }
+}
+
+/*
+ *	Records every answer given to the wrapped frontend, one per line as
+ *	"<kind> <value>", together with whether the session began with the
+ *	intro, the saved game it carried on with and the seed, level and
+ *	galaxy size of every new game. Feeding the file back through
+ *	ReplayFrontend reproduces the session exactly.
+ */
+pub struct JournalFrontend<F: Frontend> {
+    inner: F,
+    file: File,
+}
+
+impl<F: Frontend> JournalFrontend<F> {
+    /* The journal of the session before is kept as <filename>.old */
+    pub fn create(filename: &str, inner: F) -> std::io::Result<Self> {
+        if Path::new(filename).is_file() {
+            rename(filename, format!("{filename}.old"))?;
+        }
+
+        let mut file = File::create(filename)?;
+
+        writeln!(file, "{JOURNAL_HEADER}")?;
+
+        Ok(JournalFrontend { inner, file })
+    }
+
+    fn record(&mut self, kind: &str, value: &str) {
+        /* A journal we can't write must not stop the game */
+        let _ = writeln!(self.file, "{kind} {value}");
+        let _ = self.file.flush();
+    }
+}
+
+impl<F: Frontend> Frontend for JournalFrontend<F> {
+    fn print(&mut self, text: &str) {
+        self.inner.print(text);
+    }
+
+    fn event(&mut self, event: Event) {
+        self.inner.event(event);
+    }
+
+    fn refresh(&mut self, game: &Game) {
+        self.inner.refresh(game);
+    }
+
+    fn has_status_display(&self) -> bool {
+        self.inner.has_status_display()
+    }
+
+    fn mission_over(&mut self, game: &Game, score: &Score, reason: Option<LossReason>) {
+        self.inner.mission_over(game, score, reason);
+    }
+
+    fn replaying(&self) -> bool {
+        self.inner.replaying()
+    }
+
+    fn game_seed(&mut self, seed: u64) -> u64 {
+        let seed = self.inner.game_seed(seed);
+        self.record("seed", &seed.to_string());
+        seed
+    }
+
+    /* Recorded without a value when the player is asked for it */
+    fn game_difficulty(&mut self, level: Option<DifficultyLevel>) -> Option<DifficultyLevel> {
+        let level = self.inner.game_difficulty(level);
+        self.record(
+            "difficulty",
+            &level.map(|l| l.to_string()).unwrap_or_default(),
+        );
+        level
+    }
+
+    fn game_size(&mut self, size: GalaxySize) -> GalaxySize {
+        let size = self.inner.game_size(size);
+        self.record("size", &format_size(&size));
+        size
+    }
+
+    fn show_intro(&mut self, show: bool) -> bool {
+        let show = self.inner.show_intro(show);
+        self.record("intro", &show.to_string());
+        show
+    }
+
+    /* The whole save goes in, the file may be gone by the replay */
+    fn saved_game(&mut self, game: Option<Game>) -> Option<Game> {
+        let game = self.inner.saved_game(game);
+
+        if let Some(save) = game.as_ref().and_then(|g| save_to_string(g).ok()) {
+            self.record("save", &save);
+        }
+
+        game
+    }
+
+    fn any_key(&mut self, message: &str) {
+        self.inner.any_key(message);
+        self.record("key", "");
+    }
+
+    fn get_command(&mut self, message: &str) -> String {
+        let cmd = self.inner.get_command(message);
+        self.record("command", &cmd);
+        cmd
+    }
+
+    fn input_string(&mut self, message: &str) -> String {
+        let text = self.inner.input_string(message);
+        self.record("text", &text);
+        text
+    }
+
+    fn yesno(&mut self, message: &str, default: bool) -> bool {
+        let answer = self.inner.yesno(message, default);
+        self.record("yesno", &answer.to_string());
+        answer
+    }
+
+    /* A cancelled prompt is recorded without a value */
+    fn input_f32(&mut self, message: &str, min: f32, max: f32) -> Option<f32> {
+        let value = self.inner.input_f32(message, min, max);
+        self.record("f32", &value.map(|v| v.to_string()).unwrap_or_default());
+        value
+    }
+
+    fn input_i32(&mut self, message: &str, min: i32, max: i32) -> Option<i32> {
+        let value = self.inner.input_i32(message, min, max);
+        self.record("i32", &value.map(|v| v.to_string()).unwrap_or_default());
+        value
+    }
+}
+
+/*
+ *	Plays a journal back: answers come from the file and are echoed after
+ *	their prompt as they were recorded, so the transcript reads like the
+ *	original session. Once the journal runs out (or no longer matches
+ *	what the game asks for) the wrapped frontend takes over and the game
+ *	carries on from there.
+ */
+pub struct ReplayFrontend<F: Frontend> {
+    inner: F,
+    entries: Vec<(usize, String, String)>,
+    next: usize,
+    step: bool,
+    replaying: bool,
+}
+
+impl<F: Frontend> ReplayFrontend<F> {
+    pub fn open(filename: &str, step: bool, inner: F) -> std::io::Result<Self> {
+        let entries = read_to_string(filename)?
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(n, line)| {
+                let (kind, value) = line.split_once(' ').unwrap_or((line, ""));
+                (n + 1, kind.to_string(), value.to_string())
+            })
+            .collect();
+
+        Ok(ReplayFrontend {
+            inner,
+            entries,
+            next: 0,
+            step,
+            replaying: true,
+        })
+    }
+
+    /* Returns the next recorded answer if it is of the expected kind */
+    fn next_answer(&mut self, kind: &str) -> Option<String> {
+        if !self.replaying {
+            return None;
+        }
+
+        match self.entries.get(self.next) {
+            Some((_, k, value)) if k == kind => {
+                self.next += 1;
+                Some(value.clone())
+            }
+            Some((line, k, _)) => {
+                let line = *line;
+                let k = k.clone();
+                self.stop(&format!(
+                    "journal line {line} has '{k}' where '{kind}' was expected"
+                ));
+                None
+            }
+            None => {
+                self.stop("end of journal");
+                None
+            }
+        }
+    }
+
+    /* Like next_answer for entries older journals don't have, a journal
+    without one carries on with the following entry */
+    fn recorded(&mut self, kind: &str) -> Option<String> {
+        match self.entries.get(self.next) {
+            Some((_, k, value)) if self.replaying && k == kind => {
+                self.next += 1;
+                Some(value.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn stop(&mut self, reason: &str) {
+        self.replaying = false;
+        self.inner.println(&format!(
+            "\n*** Replay stopped: {reason}. You have the conn. ***\n"
+        ));
+    }
+
+    fn echo(&mut self, message: &str, answer: &str) {
+        self.inner.println(&format!("{message} {answer}"));
+    }
+
+    /* Wait for Enter before each command when stepping through a replay */
+    fn pause(&mut self) {
+        if self.step {
+            let mut line = String::new();
+            let _ = std::io::stdin().lock().read_line(&mut line);
+        }
+    }
+}
+
+impl<F: Frontend> Frontend for ReplayFrontend<F> {
+    fn print(&mut self, text: &str) {
+        self.inner.print(text);
+    }
+
+    fn event(&mut self, event: Event) {
+        self.inner.event(event);
+    }
+
+    fn refresh(&mut self, game: &Game) {
+        self.inner.refresh(game);
+    }
+
+    fn has_status_display(&self) -> bool {
+        self.inner.has_status_display()
+    }
+
+    fn mission_over(&mut self, game: &Game, score: &Score, reason: Option<LossReason>) {
+        self.inner.mission_over(game, score, reason);
+    }
+
+    fn replaying(&self) -> bool {
+        self.replaying
+    }
+
+    fn game_seed(&mut self, seed: u64) -> u64 {
+        match self.next_answer("seed").and_then(|v| v.parse().ok()) {
+            Some(recorded) => recorded,
+            None => self.inner.game_seed(seed),
+        }
+    }
+
+    /* Journals from before levels were recorded go on to ask for one */
+    fn game_difficulty(&mut self, level: Option<DifficultyLevel>) -> Option<DifficultyLevel> {
+        match self.recorded("difficulty") {
+            Some(recorded) => recorded.parse().ok(),
+            None => self.inner.game_difficulty(level),
+        }
+    }
+
+    /* Older journals were played in the galaxy the options give */
+    fn game_size(&mut self, size: GalaxySize) -> GalaxySize {
+        match self.recorded("size").and_then(|v| parse_size(&v)) {
+            Some(recorded) => recorded,
+            None => self.inner.game_size(size),
+        }
+    }
+
+    fn show_intro(&mut self, show: bool) -> bool {
+        match self.recorded("intro").and_then(|v| v.parse().ok()) {
+            Some(recorded) => recorded,
+            None => self.inner.show_intro(show),
+        }
+    }
+
+    fn saved_game(&mut self, game: Option<Game>) -> Option<Game> {
+        match self.recorded("save") {
+            Some(save) => match load_from_str(&save) {
+                Ok(recorded) => Some(recorded),
+                Err(e) => {
+                    self.stop(&format!("the journal's saved game won't load: {e}"));
+                    self.inner.saved_game(game)
+                }
+            },
+            None => self.inner.saved_game(game),
+        }
+    }
+
+    fn any_key(&mut self, message: &str) {
+        match self.next_answer("key") {
+            Some(_) => self.echo(message, ""),
+            None => self.inner.any_key(message),
+        }
+    }
+
+    fn get_command(&mut self, message: &str) -> String {
+        match self.next_answer("command") {
+            Some(cmd) => {
+                self.echo(message, &cmd);
+                self.pause();
+                cmd
+            }
+            None => self.inner.get_command(message),
+        }
+    }
+
+    fn input_string(&mut self, message: &str) -> String {
+        match self.next_answer("text") {
+            Some(text) => {
+                self.echo(message, &text);
+                text
+            }
+            None => self.inner.input_string(message),
+        }
+    }
+
+    fn yesno(&mut self, message: &str, default: bool) -> bool {
+        match self.next_answer("yesno").and_then(|v| v.parse().ok()) {
+            Some(answer) => {
+                self.echo(message, if answer { "Yes" } else { "No" });
+                answer
+            }
+            None => self.inner.yesno(message, default),
+        }
+    }
+
+    fn input_f32(&mut self, message: &str, min: f32, max: f32) -> Option<f32> {
+        match self.next_answer("f32") {
+            Some(recorded) => {
+                let value = recorded.parse::<f32>().ok();
+                self.echo(message, &recorded);
+                value
+            }
+            None => self.inner.input_f32(message, min, max),
+        }
+    }
+
+    fn input_i32(&mut self, message: &str, min: i32, max: i32) -> Option<i32> {
+        match self.next_answer("i32") {
+            Some(recorded) => {
+                let value = recorded.parse::<i32>().ok();
+                self.echo(message, &recorded);
+                value
+            }
+            None => self.inner.input_i32(message, min, max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("startrek-{}-{name}", std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /* A session the way main() runs it, with a saved game or a new one */
+    fn session(io: &mut dyn Frontend, intro_option: bool, saved: Option<Game>) {
+        if io.show_intro(intro_option) {
+            intro(io);
+        }
+
+        match io.saved_game(saved) {
+            Some(mut game) => {
+                play_game(&mut game, io);
+            }
+            None => {
+                run_game(io, 7, Some(DifficultyLevel::Expert), GalaxySize::default());
+            }
+        }
+    }
+
+    /* The transcripts of recording a session and of replaying it */
+    fn record_and_replay(name: &str, input: &str, saved: Option<Game>) -> (String, String) {
+        let journal = temp_path(name);
+        let (mut recorded, mut replayed) = (Vec::new(), Vec::new());
+
+        let batch = BatchFrontend::new(input.as_bytes(), &mut recorded);
+        session(
+            &mut JournalFrontend::create(&journal, batch).unwrap(),
+            false,
+            saved,
+        );
+
+        /* Replayed with nothing to type and none of the original options */
+        let batch = BatchFrontend::new(&b""[..], &mut replayed);
+        session(
+            &mut ReplayFrontend::open(&journal, false, batch).unwrap(),
+            true,
+            None,
+        );
+
+        fs::remove_file(&journal).unwrap();
+
+        (
+            String::from_utf8(recorded).unwrap(),
+            String::from_utf8(replayed).unwrap(),
+        )
+    }
+
+    #[test]
+    fn replay_reproduces_a_new_game() {
+        let input = "\nsrs\nnav 1 1\nnav\n2.5\n0.5\ntor\n\nxxx\nn\n";
+        let (recorded, replayed) = record_and_replay("new.journal", input, None);
+
+        assert!(recorded.contains("Course (0-9):  2.5"));
+        assert!(recorded.contains("difficulty Expert"));
+        assert_eq!(replayed, recorded);
+    }
+
+    #[test]
+    fn replay_carries_on_with_the_saved_game() {
+        let game = Game::initialize(3, Difficulty::default(), GalaxySize::default());
+        let (recorded, replayed) = record_and_replay("load.journal", "srs\nxxx\nn\n", Some(game));
+
+        assert!(recorded.contains("Stardate"));
+        assert_eq!(replayed, recorded);
+    }
+
+    #[test]
+    fn create_keeps_the_journal_before() {
+        let journal = temp_path("kept.journal");
+        let old = format!("{journal}.old");
+
+        fs::write(&journal, "# the session before\n").unwrap();
+        JournalFrontend::create(&journal, BatchFrontend::new(&b""[..], Vec::new())).unwrap();
+
+        assert_eq!(fs::read_to_string(&old).unwrap(), "# the session before\n");
+        assert_eq!(
+            fs::read_to_string(&journal).unwrap(),
+            format!("{JOURNAL_HEADER}\n")
+        );
+
+        fs::remove_file(&journal).unwrap();
+        fs::remove_file(&old).unwrap();
+    }
+}
@@ -0,0 +1,49 @@
+mod assets;
+mod batch;
+mod command;
+mod difficulty;
+mod energy;
+mod event;
+mod frontend;
+mod highscore;
+mod input;
+mod journal;
+mod parser;
+mod rnd;
+mod savegame;
+mod score;
+mod startrek;
+mod strategy;
+mod tactics;
+mod tui;
+
+pub mod prelude {
+    pub use crate::assets::*;
+    pub use crate::batch::*;
+    pub use crate::command::*;
+    pub use crate::difficulty::*;
+    pub use crate::energy::*;
+    pub use crate::event::*;
+    pub use crate::frontend::*;
+    pub use crate::highscore::*;
+    pub use crate::input::*;
+    pub use crate::journal::*;
+    pub use crate::parser::*;
+    pub use crate::rnd::*;
+    pub use crate::savegame::*;
+    pub use crate::score::*;
+    pub use crate::startrek::*;
+    pub use crate::strategy::*;
+    pub use crate::tactics::*;
+    pub use crate::tui::*;
+}
+
+pub use crate::command::{apply, start, Command, ScriptedFrontend};
+pub use crate::difficulty::{Difficulty, DifficultyLevel};
+pub use crate::energy::{Energy, Insufficient};
+pub use crate::event::Event;
+pub use crate::frontend::Frontend;
+pub use crate::startrek::{
+    DeviceType, GalaxySize, Game, Klingon, KlingonClass, Quadrant, Sector, SectorType, Ship,
+    Starbase, Vec2,
+};
@@ -1,29 +1,171 @@
-use std::env;
-use std::path::Path;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::exit;
 
-mod input;
-mod rnd;
-mod startrek;
+use clap::{Parser, ValueEnum};
 
-mod prelude {
-    pub use crate::input::*;
-    pub use crate::rnd::*;
-    pub use crate::startrek::*;
+use startrek::prelude::*;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+enum Ui {
+    #[default]
+    Console,
+    Tui,
+}
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Star Trek, the classic 1971 game")]
+struct Options {
+    /// Start the first mission in this galaxy
+    #[arg(long, value_name = "N")]
+    seed: Option<u64>,
+
+    /// novice, good, expert or emeritus, asked for when not given
+    #[arg(long, value_name = "LEVEL")]
+    difficulty: Option<DifficultyLevel>,
+
+    /// Quadrants across and down the galaxy
+    #[arg(long, value_name = "WxH", value_parser = parse_dimensions)]
+    galaxy: Option<(u32, u32)>,
+
+    /// Sectors across and down a quadrant
+    #[arg(long, value_name = "WxH", value_parser = parse_dimensions)]
+    quadrant: Option<(u32, u32)>,
+
+    /// Carry on with a saved mission
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["seed", "difficulty", "galaxy", "quadrant", "replay"]
+    )]
+    load: Option<String>,
+
+    /// Read one answer per line and print plain text
+    #[arg(long)]
+    batch: bool,
+
+    /// Skip the title and the instructions
+    #[arg(long)]
+    no_intro: bool,
+
+    /// Directory with texts to use instead of the built in ones, defaults to $TREK_DIR/assets
+    #[arg(long, value_name = "DIR")]
+    assets: Option<PathBuf>,
+
+    /// Plain prompts or the full screen display
+    #[arg(long, value_enum, default_value_t)]
+    ui: Ui,
+
+    /// Record the session here instead of startrek.journal
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    journal: Option<String>,
+
+    /// Play back a recorded session
+    #[arg(long, value_name = "FILE")]
+    replay: Option<String>,
+
+    /// Wait for Enter before each replayed command
+    #[arg(long, requires = "replay")]
+    step: bool,
+
+    /// Show the high score table and exit
+    #[arg(long)]
+    scores: bool,
 }
 
-use crate::prelude::*;
+fn fatal(message: &str) -> ! {
+    eprintln!("startrek: {message}");
+    exit(1);
+}
+
+/* Dimensions written as 12x10, width first */
+fn parse_dimensions(value: &str) -> Result<(u32, u32), String> {
+    value
+        .split_once(['x', 'X'])
+        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+        .ok_or_else(|| format!("expected width x height like 8x8, not '{value}'"))
+}
 
 fn main() {
-    let trek_dir = env::var("TREK_DIR").unwrap();
-    //println!("TREK_DIR: {}", trek_dir);
+    let options = Options::parse();
+    let mut seed = options.seed;
+
+    if options.scores {
+        show_scores(&mut ConsoleFrontend::new());
+        return;
+    }
+
+    let default = GalaxySize::default();
+    let (width, height) = options.galaxy.unwrap_or((default.width, default.height));
+    let (quadrant_width, quadrant_height) = options
+        .quadrant
+        .unwrap_or((default.quadrant_width, default.quadrant_height));
+
+    let size = match GalaxySize::new(width, height, quadrant_width, quadrant_height) {
+        Ok(size) => size,
+        Err(e) => fatal(&e),
+    };
+
+    /* Find everything we need before the screen is taken over */
+    if let Err(e) = set_assets_dir(find_assets_dir(options.assets.as_deref()).as_deref()) {
+        fatal(&e);
+    }
 
-    let root = Path::new(&trek_dir);
-    let _ = env::set_current_dir(&root).is_ok();
+    let loaded = match &options.load {
+        Some(file) => match load_game(file) {
+            Ok(game) => Some(game),
+            Err(e) => fatal(&format!("Unable to load {file}: {e}")),
+        },
+        None => None,
+    };
+
+    /* Prompts need a terminal, scripts and pipes get plain lines */
+    let frontend: Box<dyn Frontend> = if options.batch || !std::io::stdin().is_terminal() {
+        Box::new(BatchFrontend::stdio())
+    } else if options.ui == Ui::Tui {
+        Box::new(TuiFrontend::new())
+    } else {
+        Box::new(ConsoleFrontend::new())
+    };
+
+    let io: Box<dyn Frontend> = match &options.replay {
+        Some(file) => match ReplayFrontend::open(file, options.step, frontend) {
+            Ok(replay) => Box::new(replay),
+            Err(e) => fatal(&format!("Unable to read journal {file}: {e}")),
+        },
+        None => {
+            let file = options.journal.as_deref().unwrap_or(DEFAULT_JOURNAL_FILE);
+
+            match JournalFrontend::create(file, frontend) {
+                Ok(journal) => Box::new(journal),
+                Err(e) => fatal(&format!("Unable to create journal {file}: {e}")),
+            }
+        }
+    };
+
+    /* Outside the journal, so the captain's name is recorded with the rest */
+    let mut io = ScoreKeeper::new(io, scores_path());
+
+    if io.show_intro(!options.no_intro) {
+        intro(&mut io);
+    }
 
-    intro();
+    let mut loaded = io.saved_game(loaded);
 
     loop {
-        let exit_flag = run_game();
+        /* Only the first mission uses the saved game or requested seed */
+        let exit_flag = match loaded.take() {
+            Some(mut game) => {
+                short_range_scan(&mut game, &mut io);
+                play_game(&mut game, &mut io)
+            }
+            None => run_game(
+                &mut io,
+                seed.take().unwrap_or_else(random_seed),
+                options.difficulty,
+                size,
+            ),
+        };
 
         if exit_flag {
             break;
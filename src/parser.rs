@@ -0,0 +1,416 @@
+use std::collections::VecDeque;
+
+use crate::prelude::*;
+
+/*
+ *	The commands as typed at the Command? prompt: the classic three
+ *	letter keyword the engine dispatches on, the full word, and how much
+ *	of the word has to be typed. Resigning is never abbreviated.
+ */
+pub const COMMAND_WORDS: [(&str, &str, usize); 12] = [
+    ("nav", "navigate", 1),
+    ("srs", "srscan", 1),
+    ("lrs", "lrscan", 1),
+    ("pha", "phasers", 1),
+    ("tor", "torpedo", 1),
+    ("shi", "shields", 1),
+    ("dam", "damage", 1),
+    ("com", "computer", 1),
+    ("sav", "save", 1),
+    ("loa", "load", 1),
+    ("xxx", "resign", 6),
+    ("hel", "help", 1),
+];
+
+/* What `help <command>` says, by keyword */
+const COMMAND_HELP: [(&str, &str, &str); 12] = [
+    (
+        "nav",
+        "navigate <course> <warp>",
+        "Set course and engage the warp engines. Course 1 is right, 3 up,
+5 left and 7 down, fractions steer in between. Warp 1 crosses a
+quadrant, up to warp 8. Moving takes energy and time.",
+    ),
+    (
+        "srs",
+        "srscan",
+        "Short range sensor scan of the quadrant, with the ship's status.",
+    ),
+    (
+        "lrs",
+        "lrscan",
+        "Long range scan of the quadrants around the ship. Each shows as
+three digits: Klingons, starbases and stars.",
+    ),
+    (
+        "pha",
+        "phasers <units>",
+        "Fire phasers at every Klingon in the quadrant. The energy is
+shared between them and weakens with distance.",
+    ),
+    (
+        "tor",
+        "torpedo <course>",
+        "Fire a photon torpedo, courses are the same as for navigate.",
+    ),
+    (
+        "shi",
+        "shields <units>",
+        "Set the energy in the shields, taken from or given back to the
+ship's reserve.",
+    ),
+    (
+        "dam",
+        "damage",
+        "Damage control report. Docked at a starbase it offers to repair
+everything.",
+    ),
+    (
+        "com",
+        "computer <function>",
+        "Ask the library computer for one of its functions:",
+    ),
+    ("sav", "save <file>", "Save the mission to carry on later."),
+    ("loa", "load <file>", "Carry on with a saved mission."),
+    ("xxx", "resign", "Resign your command, xxx does it too."),
+    (
+        "hel",
+        "help <command>",
+        "Explain a command, on its own list them all.",
+    ),
+];
+
+/* A command line split into its keyword and the answers typed after it */
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandLine {
+    pub keyword: &'static str,
+    pub args: Vec<String>,
+}
+
+/*
+ *	Full words, unique prefixes of them ("phaser", "tor") and the old
+ *	keywords all work. Err holds what to tell the captain, None for a
+ *	line with nothing on it.
+ */
+pub fn parse_command(line: &str) -> Result<Option<CommandLine>, String> {
+    let mut words = line.split_whitespace();
+
+    let Some(word) = words.next() else {
+        return Ok(None);
+    };
+
+    let word = word.to_lowercase();
+
+    let matches: Vec<&(&str, &str, usize)> = COMMAND_WORDS
+        .iter()
+        .filter(|(keyword, name, _)| word == *keyword || name.starts_with(&word))
+        .collect();
+
+    match matches[..] {
+        [(keyword, name, min)] if word != *keyword && word.len() < *min => {
+            Err(format!("Type '{name}' in full to do that"))
+        }
+        [(keyword, _, _)] => Ok(Some(CommandLine {
+            keyword,
+            args: words.map(str::to_string).collect(),
+        })),
+        [] => Err(format!("Unknown command '{word}'")),
+        _ => Err(format!(
+            "'{word}' could be {}",
+            matches
+                .iter()
+                .map(|(_, name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/*
+ *	Answers the prompts of a command from the values typed after it on
+ *	the command line, echoing them like the player had typed them at the
+ *	prompt. Once they run out, or one doesn't fit what is asked, the
+ *	wrapped frontend asks the player for the rest.
+ */
+pub struct InlineAnswers<'a> {
+    io: &'a mut dyn Frontend,
+    answers: VecDeque<String>,
+}
+
+impl<'a> InlineAnswers<'a> {
+    pub fn new(io: &'a mut dyn Frontend, answers: Vec<String>) -> Self {
+        InlineAnswers {
+            io,
+            answers: answers.into(),
+        }
+    }
+
+    /* Values nobody asked for */
+    pub fn leftover(&self) -> Vec<String> {
+        self.answers.iter().cloned().collect()
+    }
+
+    fn next_answer<T>(&mut self, parse: impl Fn(&str) -> Result<T, String>) -> Option<T> {
+        let answer = self.answers.pop_front()?;
+
+        match parse(&answer) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                /* The rest was meant for this prompt and the ones after */
+                self.io.println(&format!("'{answer}': {e}"));
+                self.answers.clear();
+                None
+            }
+        }
+    }
+
+    fn next_number<T>(&mut self, min: T, max: T) -> Option<T>
+    where
+        T: std::str::FromStr + PartialOrd + std::fmt::Display + Copy,
+    {
+        /* Arguments are never empty, check_number only gives Some */
+        self.next_answer(|a| check_number(a, min, max))?
+    }
+
+    fn echo(&mut self, message: &str, answer: &str) {
+        self.io.println(&format!("{message} {answer}"));
+    }
+}
+
+impl Frontend for InlineAnswers<'_> {
+    fn print(&mut self, text: &str) {
+        self.io.print(text);
+    }
+
+    fn println(&mut self, text: &str) {
+        self.io.println(text);
+    }
+
+    fn event(&mut self, event: Event) {
+        self.io.event(event);
+    }
+
+    fn refresh(&mut self, game: &Game) {
+        self.io.refresh(game);
+    }
+
+    fn has_status_display(&self) -> bool {
+        self.io.has_status_display()
+    }
+
+    fn mission_over(&mut self, game: &Game, score: &Score, reason: Option<LossReason>) {
+        self.io.mission_over(game, score, reason);
+    }
+
+    fn replaying(&self) -> bool {
+        self.io.replaying()
+    }
+
+    fn any_key(&mut self, message: &str) {
+        self.io.any_key(message);
+    }
+
+    fn get_command(&mut self, message: &str) -> String {
+        self.io.get_command(message)
+    }
+
+    fn input_string(&mut self, message: &str) -> String {
+        match self.next_answer(|a| Ok(a.to_string())) {
+            Some(text) => {
+                self.echo(message, &text);
+                text
+            }
+            None => self.io.input_string(message),
+        }
+    }
+
+    fn yesno(&mut self, message: &str, default: bool) -> bool {
+        let answer = self.next_answer(|a| match a.to_lowercase().as_str() {
+            "y" | "yes" | "true" => Ok(true),
+            "n" | "no" | "false" => Ok(false),
+            _ => Err("Please answer yes or no".to_string()),
+        });
+
+        match answer {
+            Some(answer) => {
+                self.echo(message, if answer { "Yes" } else { "No" });
+                answer
+            }
+            None => self.io.yesno(message, default),
+        }
+    }
+
+    fn input_f32(&mut self, message: &str, min: f32, max: f32) -> Option<f32> {
+        match self.next_number(min, max) {
+            Some(value) => {
+                self.echo(message, &format!("{:.2}", value));
+                Some(value)
+            }
+            None => self.io.input_f32(message, min, max),
+        }
+    }
+
+    fn input_i32(&mut self, message: &str, min: i32, max: i32) -> Option<i32> {
+        match self.next_number(min, max) {
+            Some(value) => {
+                self.echo(message, &value.to_string());
+                Some(value)
+            }
+            None => self.io.input_i32(message, min, max),
+        }
+    }
+}
+
+/* `help` on its own lists the commands, with a command it explains it */
+pub fn show_help(io: &mut dyn Frontend, command: Option<&str>) {
+    let keyword = match command.map(parse_command) {
+        None | Some(Ok(None)) => {
+            show_file(io, "commands.txt");
+            return;
+        }
+        Some(Ok(Some(command))) => command.keyword,
+        Some(Err(e)) => {
+            io.println(&e);
+            return;
+        }
+    };
+
+    if let Some((_, usage, text)) = COMMAND_HELP.iter().find(|(k, _, _)| *k == keyword) {
+        io.println(&format!("{usage}\n{text}"));
+    }
+
+    if keyword == "com" {
+        for (i, name) in COMPUTER_FUNCTIONS.iter().enumerate() {
+            io.println(&format!("  {i} = {name}"));
+        }
+    }
+}
+
+/*
+ *	Completions for a command line typed up to `pos`: where the word
+ *	being completed starts, and what can replace it together with what
+ *	to show for it. Commands complete to their full word, the computer
+ *	to its function numbers and help to commands again.
+ */
+pub fn complete_command(line: &str, pos: usize) -> (usize, Vec<(String, String)>) {
+    let line = &line[..pos];
+    let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let word = line[start..].to_lowercase();
+    let before: Vec<&str> = line[..start].split_whitespace().collect();
+
+    let commands = || {
+        COMMAND_WORDS
+            .iter()
+            .filter(|(_, name, _)| name.starts_with(&word))
+            .map(|(_, name, _)| (name.to_string(), name.to_string()))
+            .collect()
+    };
+
+    let keyword = match before.first().map(|w| parse_command(w)) {
+        None => return (start, commands()),
+        Some(Ok(Some(command))) => command.keyword,
+        _ => return (start, Vec::new()),
+    };
+
+    let candidates = match (keyword, before.len()) {
+        ("hel", 1) => commands(),
+        ("com", 1) => COMPUTER_FUNCTIONS
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (i.to_string(), format!("{i} = {name}")))
+            .filter(|(n, _)| n.starts_with(&word))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    (start, candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyword(line: &str) -> Option<&'static str> {
+        parse_command(line).ok().flatten().map(|c| c.keyword)
+    }
+
+    #[test]
+    fn parses_keywords_words_and_prefixes() {
+        assert_eq!(keyword("nav"), Some("nav"));
+        assert_eq!(keyword("NAVIGATE"), Some("nav"));
+        assert_eq!(keyword("phaser"), Some("pha"));
+        assert_eq!(keyword("lr"), Some("lrs"));
+        assert_eq!(keyword("xxx"), Some("xxx"));
+        assert_eq!(keyword("resign"), Some("xxx"));
+        assert_eq!(parse_command("   "), Ok(None));
+    }
+
+    #[test]
+    fn refuses_words_that_only_start_with_a_keyword() {
+        for word in ["navel", "comet", "loan", "xxxyz", "torpedoes"] {
+            assert_eq!(
+                parse_command(word),
+                Err(format!("Unknown command '{word}'"))
+            );
+        }
+    }
+
+    #[test]
+    fn resigning_is_never_abbreviated() {
+        assert_eq!(
+            parse_command("res"),
+            Err("Type 'resign' in full to do that".to_string())
+        );
+    }
+
+    #[test]
+    fn ambiguous_prefixes_list_the_commands() {
+        assert_eq!(
+            parse_command("s"),
+            Err("'s' could be srscan, shields, save".to_string())
+        );
+    }
+
+    #[test]
+    fn splits_off_inline_answers() {
+        assert_eq!(
+            parse_command("nav 1.5  2"),
+            Ok(Some(CommandLine {
+                keyword: "nav",
+                args: vec!["1.5".to_string(), "2".to_string()],
+            }))
+        );
+    }
+
+    fn completions(line: &str) -> (usize, Vec<String>) {
+        let (start, candidates) = complete_command(line, line.len());
+
+        (start, candidates.into_iter().map(|(c, _)| c).collect())
+    }
+
+    #[test]
+    fn completes_commands() {
+        assert_eq!(
+            completions(""),
+            (0, COMMAND_WORDS.map(|(_, n, _)| n.to_string()).to_vec())
+        );
+        assert_eq!(completions("sh"), (0, vec!["shields".to_string()]));
+        assert_eq!(completions("help lr"), (5, vec!["lrscan".to_string()]));
+        assert_eq!(completions("zz"), (0, Vec::new()));
+    }
+
+    #[test]
+    fn completes_computer_functions() {
+        let (start, candidates) = completions("computer ");
+
+        assert_eq!(start, 9);
+        assert_eq!(candidates.len(), COMPUTER_FUNCTIONS.len());
+        assert_eq!(completions("com 1"), (4, vec!["1".to_string()]));
+    }
+
+    #[test]
+    fn completes_nothing_after_other_commands() {
+        assert_eq!(completions("nav 1"), (4, Vec::new()));
+        assert_eq!(completions("navel 1"), (6, Vec::new()));
+    }
+}
@@ -1,10 +1,28 @@
 use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+/*
+ *	The random number generator owned by a game. ChaCha8 output is
+ *	portable, so the same seed always builds the same galaxy
+ */
+pub type GameRng = ChaCha8Rng;
+
+pub fn new_rng(seed: u64) -> GameRng {
+    ChaCha8Rng::seed_from_u64(seed)
+}
+
+/*
+ *	Pick a seed for a game when the player didn't ask for one
+ */
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
 
 /*
  *	Returns an integer from 0 to spread
  */
-pub fn get_rand(spread: i32) -> i32 {
-    let y: f64 = rand::thread_rng().gen(); // generates a float between 0 and 1
+pub fn get_rand(rng: &mut GameRng, spread: i32) -> i32 {
+    let y: f64 = rng.gen(); // generates a float between 0 and 1
 
     let r: i32 = (y * spread as f64) as i32;
     r + 1
@@ -13,14 +31,14 @@ pub fn get_rand(spread: i32) -> i32 {
 /*
  *	Get a random co-ordinate
  */
-pub fn rand8() -> i32 {
-    get_rand(8)
+pub fn rand8(rng: &mut GameRng) -> i32 {
+    get_rand(rng, 8)
 }
 /*
  *	Get a random co-ordinate
  */
-pub fn get_rand8() -> i32 {
-    let y: f64 = rand::thread_rng().gen(); // generates a float between 0 and 1
+pub fn get_rand8(rng: &mut GameRng) -> i32 {
+    let y: f64 = rng.gen(); // generates a float between 0 and 1
 
     (y * 8.0) as i32
 }
@@ -28,6 +46,6 @@ pub fn get_rand8() -> i32 {
 /*
  *	Get a random co-ordinate
  */
-pub fn get_randf32() -> f32 {
-    rand::thread_rng().gen::<f32>()
+pub fn get_randf32(rng: &mut GameRng) -> f32 {
+    rng.gen::<f32>()
 }
@@ -0,0 +1,379 @@
+use std::fmt;
+use std::fs;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::prelude::*;
+
+pub const DEFAULT_SAVE_FILE: &str = "startrek.sav";
+
+/*
+ *	Version of the save file layout. Bump it whenever Game changes in a
+ *	way serde defaults can't absorb, and teach migrate() how to bring the
+ *	previous version up to date so old saves keep loading. A save of
+ *	each version goes in tests/saves for the tests to load.
+ */
+pub const SAVE_VERSION: u64 = 5;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Format(serde_json::Error),
+    MissingVersion,
+    UnsupportedVersion(u64),
+    MapSize,
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "{e}"),
+            SaveError::Format(e) => write!(f, "corrupted save file: {e}"),
+            SaveError::MissingVersion => write!(f, "not a Star Trek save file"),
+            SaveError::UnsupportedVersion(v) => write!(
+                f,
+                "save file version {v} is newer than this game (version {SAVE_VERSION})"
+            ),
+            SaveError::MapSize => {
+                write!(f, "corrupted save file: maps don't match the galaxy size")
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for SaveError {
+    fn from(e: std::io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(e: serde_json::Error) -> Self {
+        SaveError::Format(e)
+    }
+}
+
+#[derive(Serialize)]
+struct SaveFileRef<'a> {
+    version: u64,
+    game: &'a Game,
+}
+
+/* What a save file holds, all on one line */
+pub fn save_to_string(game: &Game) -> Result<String, SaveError> {
+    let save = SaveFileRef {
+        version: SAVE_VERSION,
+        game,
+    };
+
+    Ok(serde_json::to_string(&save)?)
+}
+
+pub fn save_game(game: &Game, filename: &str) -> Result<(), SaveError> {
+    fs::write(filename, save_to_string(game)?)?;
+
+    Ok(())
+}
+
+pub fn load_game(filename: &str) -> Result<Game, SaveError> {
+    load_from_str(&fs::read_to_string(filename)?)
+}
+
+pub fn load_from_str(contents: &str) -> Result<Game, SaveError> {
+    let mut save: Value = serde_json::from_str(contents)?;
+
+    let version = save
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or(SaveError::MissingVersion)?;
+
+    if version > SAVE_VERSION {
+        return Err(SaveError::UnsupportedVersion(version));
+    }
+
+    migrate(&mut save, version);
+
+    let mut game: Game = serde_json::from_value(save["game"].take())?;
+
+    /* Everything indexes the maps by the galaxy size */
+    let size = game.size;
+
+    if game.quadrant_map.len() != size.height as usize
+        || game
+            .quadrant_map
+            .iter()
+            .any(|r| r.len() != size.width as usize)
+        || game.sector_map.len() != size.rows() as usize
+        || game
+            .sector_map
+            .iter()
+            .any(|r| r.len() != size.columns() as usize)
+    {
+        return Err(SaveError::MapSize);
+    }
+
+    /* Device names are not saved, they come from the device table */
+    for device in game.ship.devices.values_mut() {
+        device.name = DEVICE_NAME[device.device_type as usize];
+    }
+
+    Ok(game)
+}
+
+/* Upgrade an older save, one version at a time */
+fn migrate(save: &mut Value, version: u64) {
+    let game = &mut save["game"];
+
+    /* Games saved before difficulty levels were all played at Good, the
+    fields added since are patched in below like for any other save */
+    if !game.get("difficulty").is_some_and(Value::is_object) {
+        set_field(
+            game,
+            "difficulty",
+            serde_json::to_value(Difficulty::default()).unwrap_or_default(),
+        );
+    }
+
+    let level: DifficultyLevel =
+        serde_json::from_value(game["difficulty"]["level"].clone()).unwrap_or_default();
+    let defaults = Difficulty::new(level);
+
+    /* Version 2 gave Klingons tactics, the ones of the saved level */
+    if version < 2 {
+        set_field(
+            &mut game["difficulty"],
+            "tactics",
+            serde_json::to_value(defaults.tactics).unwrap_or_default(),
+        );
+    }
+
+    /* Version 3 moves the Klingon fleet from the stardate the game was
+    saved at and keeps what the sensors last saw of each quadrant */
+    if version < 3 {
+        set_field(
+            &mut game["difficulty"],
+            "strategy",
+            serde_json::to_value(defaults.strategy).unwrap_or_default(),
+        );
+
+        let stardate = game["stardate"].clone();
+        set_field(game, "fleet_stardate", stardate);
+
+        if let Value::Array(rows) = &mut game["quadrant_map"] {
+            for quadrant in rows.iter_mut().flat_map(|row| row.as_array_mut()).flatten() {
+                if quadrant["visited"] == Value::Bool(true) {
+                    let code = ["klingons", "starbases", "stars"]
+                        .iter()
+                        .map(|k| (quadrant[k].as_u64().unwrap_or(0) & 0x0F).to_string())
+                        .collect::<String>();
+
+                    set_field(quadrant, "record", Value::String(code));
+                }
+            }
+        }
+    }
+
+    /* Version 4 lets Klingons besiege starbases */
+    if version < 4 {
+        set_field(
+            &mut game["difficulty"]["strategy"],
+            "siege_days",
+            serde_json::to_value(defaults.strategy.siege_days).unwrap_or_default(),
+        );
+    }
+
+    /* Version 5 brought Commanders, older galaxies were built without */
+    if version < 5 {
+        set_field(&mut game["difficulty"], "commanders", Value::from(0));
+        set_field(
+            &mut game["difficulty"],
+            "super_commander",
+            Value::from(false),
+        );
+    }
+}
+
+fn set_field(object: &mut Value, name: &str, value: Value) {
+    if let Value::Object(fields) = object {
+        fields.insert(name.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /*
+     *	tests/saves holds a save of every version, each written unchanged
+     *	by the game at the commit that introduced that version: v1 by
+     *	user-003 (seed 7, from before difficulty levels and galaxy sizes),
+     *	v2 to v5 by user-022 to user-025 (seed 11, --difficulty expert
+     *	--galaxy 4x4 --quadrant 6x6). Each is a few moves into the game.
+     */
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/saves/{name}", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    fn load_version(version: u64) -> Game {
+        load_game(&fixture(&format!("v{version}.json"))).unwrap()
+    }
+
+    fn expert() -> Difficulty {
+        Difficulty::new(DifficultyLevel::Expert)
+    }
+
+    fn temp_file(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("startrek-{}-{name}", std::process::id()));
+
+        fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn all_warships(game: &Game) -> bool {
+        game.klingons
+            .iter()
+            .all(|k| k.class == KlingonClass::Warship)
+    }
+
+    #[test]
+    fn loads_every_version() {
+        for version in 1..=SAVE_VERSION {
+            let game = load_version(version);
+
+            assert_eq!(game.ship.devices.len(), DEVICE_NAME.len());
+            assert_eq!(
+                game.klingons.iter().filter(|k| !k.destroyed).count() as u32,
+                game.klingons_left
+            );
+        }
+    }
+
+    #[test]
+    fn version_1_was_a_classic_game() {
+        let game = load_version(1);
+        let good = Difficulty::new(DifficultyLevel::Good);
+
+        assert_eq!(game.seed, 7);
+        assert_eq!(game.size, GalaxySize::default());
+        assert_eq!(game.difficulty.level, DifficultyLevel::Good);
+        assert_eq!(game.difficulty.klingon_energy, good.klingon_energy);
+        assert_eq!(game.difficulty.tactics, good.tactics);
+        assert_eq!(game.difficulty.strategy, good.strategy);
+        assert_eq!(game.difficulty.commanders, 0);
+        assert!(!game.difficulty.super_commander);
+        assert!(all_warships(&game));
+    }
+
+    #[test]
+    fn versions_before_3_move_the_fleet_from_the_saved_stardate() {
+        for version in [1, 2] {
+            let game = load_version(version);
+
+            assert_eq!(game.fleet_stardate, game.stardate);
+
+            for quadrant in game.quadrant_map.iter().flatten() {
+                assert_eq!(quadrant.visited, quadrant.record.is_some());
+            }
+
+            let q = game.ship.get_quadrant_position(game.size);
+            let quadrant = &game.quadrant_map[q.x as usize][q.y as usize];
+            assert_eq!(
+                quadrant.record,
+                Some(format!(
+                    "{}{}{}",
+                    quadrant.klingons, quadrant.starbases, quadrant.stars
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn version_2_gets_the_strategy_of_its_level() {
+        let game = load_version(2);
+
+        assert_eq!(game.size, GalaxySize::new(4, 4, 6, 6).unwrap());
+        assert_eq!(game.difficulty.level, DifficultyLevel::Expert);
+        assert_eq!(game.difficulty.tactics, expert().tactics);
+        assert_eq!(game.difficulty.strategy, expert().strategy);
+    }
+
+    #[test]
+    fn version_3_keeps_the_fleet_and_records() {
+        let game = load_version(3);
+
+        assert!(game.fleet_stardate < game.stardate);
+        assert_eq!(
+            game.difficulty.strategy.siege_days,
+            expert().strategy.siege_days
+        );
+        assert!(game
+            .quadrant_map
+            .iter()
+            .flatten()
+            .any(|q| q.record.is_some()));
+    }
+
+    #[test]
+    fn versions_before_5_have_no_commanders() {
+        for version in 1..5 {
+            let game = load_version(version);
+
+            assert_eq!(game.difficulty.commanders, 0);
+            assert!(!game.difficulty.super_commander);
+            assert!(game.starbases.iter().all(|s| s.siege.is_none()));
+            assert!(all_warships(&game));
+        }
+    }
+
+    #[test]
+    fn version_5_loads_as_saved() {
+        let game = load_version(5);
+        let count = |class| game.klingons.iter().filter(|k| k.class == class).count();
+
+        assert_eq!(game.difficulty.level, DifficultyLevel::Expert);
+        assert_eq!(count(KlingonClass::Commander), 3);
+        assert_eq!(count(KlingonClass::SuperCommander), 1);
+    }
+
+    #[test]
+    fn saved_game_loads_the_same() {
+        let game = Game::initialize(42, expert(), GalaxySize::default());
+        let file = temp_file("round-trip.sav", "");
+
+        save_game(&game, &file).unwrap();
+        let loaded = load_game(&file).unwrap();
+        fs::remove_file(&file).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&game).unwrap()
+        );
+    }
+
+    #[test]
+    fn refuses_what_it_cannot_load() {
+        let newer = temp_file(
+            "newer.sav",
+            &format!(r#"{{"version":{}}}"#, SAVE_VERSION + 1),
+        );
+        let other = temp_file("other.sav", r#"{"game":{}}"#);
+
+        assert!(matches!(
+            load_game(&newer),
+            Err(SaveError::UnsupportedVersion(v)) if v == SAVE_VERSION + 1
+        ));
+        assert!(matches!(load_game(&other), Err(SaveError::MissingVersion)));
+
+        let mut save: Value =
+            serde_json::from_str(&fs::read_to_string(fixture("v5.json")).unwrap()).unwrap();
+        save["game"]["quadrant_map"].as_array_mut().unwrap().pop();
+        let shrunk = temp_file("shrunk.sav", &save.to_string());
+
+        assert!(matches!(load_game(&shrunk), Err(SaveError::MapSize)));
+
+        for file in [newer, other, shrunk] {
+            fs::remove_file(file).unwrap();
+        }
+    }
+}
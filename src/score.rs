@@ -0,0 +1,232 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/* Points for each part of the final score */
+const KLINGON_POINTS: i32 = 10;
+const KILL_RATE_POINTS: f32 = 500.0;
+const STARBASE_POINTS: i32 = -100;
+const ENERGY_PER_POINT: u32 = 100;
+const VICTORY_POINTS: i32 = 100;
+const SHIP_LOST_POINTS: i32 = -200;
+
+/*
+ *	How well a mission went, worked out when it ends. Each part of the
+ *	score is kept so the breakdown can be shown to the captain.
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    pub klingons_destroyed: u32,
+    pub starbases_lost: u32,
+    pub stardates_used: f32,
+    pub stardates_allowed: f32,
+    pub energy_spent: u32,
+    pub won: bool,
+    pub survived: bool,
+}
+
+impl Score {
+    pub fn new(game: &Game, survived: bool) -> Score {
+        Score {
+            klingons_destroyed: game.total_klingons - game.klingons_left,
+            starbases_lost: game.total_starbases - game.starbases_left,
+            stardates_used: game.stardate - game.time_start,
+            stardates_allowed: game.time_up - game.time_start,
+            energy_spent: game.energy_used,
+            won: game.klingons_left == 0,
+            survived,
+        }
+    }
+
+    /* Klingons destroyed per stardate, a mission ending on its first
+    stardate counts as taking one */
+    pub fn kill_rate(&self) -> f32 {
+        self.klingons_destroyed as f32 / self.stardates_used.max(1.0)
+    }
+
+    /* The classic rating shown when the last Klingon is destroyed */
+    pub fn efficiency(&self) -> f32 {
+        1000.0 * self.kill_rate() * self.kill_rate()
+    }
+
+    pub fn klingon_points(&self) -> i32 {
+        self.klingons_destroyed as i32 * KLINGON_POINTS
+    }
+
+    pub fn kill_rate_points(&self) -> i32 {
+        (self.kill_rate() * KILL_RATE_POINTS) as i32
+    }
+
+    pub fn starbase_points(&self) -> i32 {
+        self.starbases_lost as i32 * STARBASE_POINTS
+    }
+
+    pub fn energy_points(&self) -> i32 {
+        -((self.energy_spent / ENERGY_PER_POINT) as i32)
+    }
+
+    pub fn outcome_points(&self) -> i32 {
+        if !self.survived {
+            SHIP_LOST_POINTS
+        } else if self.won {
+            VICTORY_POINTS
+        } else {
+            0
+        }
+    }
+
+    pub fn total(&self) -> i32 {
+        self.klingon_points()
+            + self.kill_rate_points()
+            + self.starbase_points()
+            + self.energy_points()
+            + self.outcome_points()
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.won {
+            writeln!(f, "Your efficiency rating is {:.2}\n", self.efficiency())?;
+        }
+
+        writeln!(f, "Mission score:")?;
+        writeln!(
+            f,
+            "  Klingons destroyed     {:>8}          {:>6}",
+            self.klingons_destroyed,
+            self.klingon_points()
+        )?;
+        writeln!(
+            f,
+            "  Klingons per stardate  {:>8.2}          {:>6}",
+            self.kill_rate(),
+            self.kill_rate_points()
+        )?;
+        writeln!(
+            f,
+            "    ({:.1} of {:.1} stardates used)",
+            self.stardates_used, self.stardates_allowed
+        )?;
+        writeln!(
+            f,
+            "  Starbases lost         {:>8}          {:>6}",
+            self.starbases_lost,
+            self.starbase_points()
+        )?;
+        writeln!(
+            f,
+            "  Energy spent           {:>8}          {:>6}",
+            self.energy_spent,
+            self.energy_points()
+        )?;
+
+        if !self.survived {
+            writeln!(
+                f,
+                "  Enterprise lost                          {:>6}",
+                self.outcome_points()
+            )?;
+        } else if self.won {
+            writeln!(
+                f,
+                "  Victory bonus                            {:>6}",
+                self.outcome_points()
+            )?;
+        }
+
+        writeln!(f, "                                           ------")?;
+        writeln!(
+            f,
+            "  Total                                    {:>6}",
+            self.total()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn victory() -> Score {
+        Score {
+            klingons_destroyed: 10,
+            starbases_lost: 1,
+            stardates_used: 20.0,
+            stardates_allowed: 30.0,
+            energy_spent: 1234,
+            won: true,
+            survived: true,
+        }
+    }
+
+    #[test]
+    fn total_adds_up_every_part() {
+        let score = victory();
+
+        assert_eq!(score.klingon_points(), 100);
+        assert_eq!(score.kill_rate_points(), 250);
+        assert_eq!(score.starbase_points(), -100);
+        assert_eq!(score.energy_points(), -12);
+        assert_eq!(score.outcome_points(), 100);
+        assert_eq!(score.total(), 338);
+        assert_eq!(score.efficiency(), 250.0);
+    }
+
+    #[test]
+    fn losing_the_ship_costs_the_victory() {
+        let score = Score {
+            won: false,
+            survived: false,
+            ..victory()
+        };
+
+        assert_eq!(score.outcome_points(), -200);
+        assert_eq!(score.total(), 38);
+
+        let score = Score {
+            won: false,
+            ..victory()
+        };
+
+        assert_eq!(score.outcome_points(), 0);
+        assert_eq!(score.total(), 238);
+    }
+
+    #[test]
+    fn first_stardate_counts_as_one() {
+        let score = Score {
+            klingons_destroyed: 2,
+            stardates_used: 0.25,
+            ..victory()
+        };
+
+        assert_eq!(score.kill_rate(), 2.0);
+        assert_eq!(score.kill_rate_points(), 1000);
+    }
+
+    #[test]
+    fn breakdown_shows_each_part() {
+        let text = victory().to_string();
+
+        assert!(text.starts_with("Your efficiency rating is 250.00\n"));
+        assert!(text.contains("  Klingons destroyed           10             100\n"));
+        assert!(text.contains("    (20.0 of 30.0 stardates used)\n"));
+        assert!(text.contains("  Victory bonus                               100\n"));
+        assert!(text.ends_with("  Total                                       338\n"));
+        assert!(!text.contains("Enterprise lost"));
+
+        let text = Score {
+            won: false,
+            survived: false,
+            ..victory()
+        }
+        .to_string();
+
+        assert!(text.starts_with("Mission score:\n"));
+        assert!(text.contains("  Enterprise lost                            -200\n"));
+        assert!(!text.contains("Victory bonus"));
+    }
+}
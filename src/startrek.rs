@@ -1,31 +1,27 @@
 use std::collections::HashMap;
-use std::io::Write;
 
-use std::fs::read_to_string;
+use serde::{Deserialize, Serialize};
 
 use crate::prelude::*;
 
 pub const MAXROW: u8 = 24;
 pub const MAXCOL: u8 = 80;
 
-pub const QUADRANT_WIDTH: u32 = 8;
-pub const QUADRANT_HEIGHT: u32 = 8;
+/* The classic galaxy: 8x8 quadrants of 8x8 sectors */
+pub const GALAXY_WIDTH: u32 = 8; /* Quadrants across the galaxy */
+pub const GALAXY_HEIGHT: u32 = 8; /* Quadrants down the galaxy */
+pub const QUADRANT_WIDTH: u32 = 8; /* Sectors across a quadrant */
+pub const QUADRANT_HEIGHT: u32 = 8; /* Sectors down a quadrant */
 
-const SRS_1: &str = "------------------------";
+const SRS_1: &str = "---"; /* Per sector of a quadrant */
 
-const TILESTR: [&str; 5] = ["   ", " * ", ">!<", "+K+", "<*>"];
+const TILESTR: [&str; 7] = ["   ", " * ", ">!<", "+K+", "<*>", "+C+", "+S+"];
 
 const LRS_1: &str = "-------------------";
 
-const DCR_1: &str = "Damage Control report:";
-
-const GR_1: &str = "   ----- ----- ----- ----- ----- ----- ----- -----";
-
-const GM_1: &str = "  ----- ----- ----- ----- ----- ----- ----- -----";
-const DIST_1: &str = "  DISTANCE = %s\n\n";
 const STR_S: &str = "s";
 
-const DEVICE_NAME: [&str; 8] = [
+pub const DEVICE_NAME: [&str; 8] = [
     "Warp engines",
     "Short range sensors",
     "Long range sensors",
@@ -61,13 +57,110 @@ pub const STARTING_ENERGY: u32 = 3000; /* Starting Energy */
 // energy0
 pub const DEFAULT_PHOTON_TORPEDO_CAPACITY: u32 = 10; /* Photon Torpedo capacity */
 //torps0
+pub const TORPEDO_ENERGY: u32 = 2; /* Energy to launch a torpedo */
 
-#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub struct Vec2 {
     pub x: i32,
     pub y: i32,
 }
 
+/* One sector step for courses 1 to 9, x grows down and y to the right
+like on the sensor displays, so course 1 is right and 3 is up */
+const COURSE_VECTOR: [Vec2; 9] = [
+    Vec2 { x: 0, y: 1 },
+    Vec2 { x: -1, y: 1 },
+    Vec2 { x: -1, y: 0 },
+    Vec2 { x: -1, y: -1 },
+    Vec2 { x: 0, y: -1 },
+    Vec2 { x: 1, y: -1 },
+    Vec2 { x: 1, y: 0 },
+    Vec2 { x: 1, y: 1 },
+    Vec2 { x: 0, y: 1 },
+];
+
+/*
+ *	The sectors something travelling along a course passes through, one
+ *	step at a time. Courses in between the main ones blend the step of
+ *	their two neighbours and the position is tracked in fractions of a
+ *	sector, so every step still lands next to the previous sector and
+ *	nothing can be jumped over.
+ */
+pub struct Track {
+    x: f32,
+    y: f32,
+    dx: f32,
+    dy: f32,
+}
+
+impl Track {
+    pub fn new(from: &Vec2, course: f32) -> Track {
+        let course = course.clamp(1.0, 9.0);
+        let i = (course as usize).min(8);
+        let f = course - i as f32;
+
+        let a = COURSE_VECTOR[i - 1];
+        let b = COURSE_VECTOR[i];
+
+        Track {
+            x: from.x as f32,
+            y: from.y as f32,
+            dx: a.x as f32 + (b.x - a.x) as f32 * f,
+            dy: a.y as f32 + (b.y - a.y) as f32 * f,
+        }
+    }
+}
+
+impl Iterator for Track {
+    type Item = Vec2;
+
+    fn next(&mut self) -> Option<Vec2> {
+        self.x += self.dx;
+        self.y += self.dy;
+
+        Some(Vec2 {
+            x: (self.x + 0.5).floor() as i32,
+            y: (self.y + 0.5).floor() as i32,
+        })
+    }
+}
+
+impl Vec2 {
+    /*
+     *	Course and warp factor that take a ship from here to `to`, both
+     *	absolute sector positions so it works across quadrants too.
+     *
+     *	Courses in between the eight main ones lie along the sides of a
+     *	square around the ship, which is how the original game steers, and
+     *	one warp factor covers the width of a quadrant of the given size.
+     *	None if there is nowhere to go.
+     */
+    pub fn course_to(&self, to: &Vec2, size: GalaxySize) -> Option<(f32, f32)> {
+        /* Right and up are positive here */
+        let e = (to.y - self.y) as f32;
+        let n = (self.x - to.x) as f32;
+        let m = e.abs().max(n.abs());
+
+        if m == 0.0 {
+            return None;
+        }
+
+        let t = if e == m && n >= 0.0 {
+            n / m
+        } else if n == m {
+            1.0 + (m - e) / m
+        } else if -e == m {
+            3.0 + (m - n) / m
+        } else if -n == m {
+            5.0 + (e + m) / m
+        } else {
+            7.0 + (n + m) / m
+        };
+
+        Some((1.0 + t, m / size.quadrant_width as f32))
+    }
+}
+
 pub trait HasPosition {
     fn get_position(&self) -> &Vec2;
 
@@ -94,13 +187,8 @@ pub trait Moveable<T: HasPosition = Self>: HasPosition {
     }
 }
 
-#[derive(Debug, Default, PartialEq, Clone, Copy)]
-pub struct Stardate {
-    pub year: i32,
-    pub date: i32,
-}
-
-#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum SectorType {
     #[default]
     SPACE,
@@ -108,32 +196,137 @@ pub enum SectorType {
     BASE,
     KLINGON,
     SHIP = 4,
+    COMMANDER,
+    SUPERCOMMANDER,
+}
+
+impl SectorType {
+    /* How the short range sensors draw this sector */
+    pub fn glyph(&self) -> &'static str {
+        TILESTR[*self as usize]
+    }
+
+    /* A Klingon of any class */
+    pub fn is_klingon(&self) -> bool {
+        matches!(
+            self,
+            SectorType::KLINGON | SectorType::COMMANDER | SectorType::SUPERCOMMANDER
+        )
+    }
+}
+
+/*
+ *	Dimensions of a galaxy, chosen when it is built. Like everywhere
+ *	else x runs down and y across, so heights go with x.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GalaxySize {
+    pub width: u32,  /* Quadrants across */
+    pub height: u32, /* Quadrants down */
+    pub quadrant_width: u32,
+    pub quadrant_height: u32,
+}
+
+impl Default for GalaxySize {
+    fn default() -> Self {
+        GalaxySize {
+            width: GALAXY_WIDTH,
+            height: GALAXY_HEIGHT,
+            quadrant_width: QUADRANT_WIDTH,
+            quadrant_height: QUADRANT_HEIGHT,
+        }
+    }
+}
+
+impl GalaxySize {
+    /* Quadrants need room for the Enterprise and a few neighbours, and
+    the sector map has to fit on a screen */
+    pub const QUADRANTS: std::ops::RangeInclusive<u32> = 2..=16;
+    pub const SECTORS: std::ops::RangeInclusive<u32> = 4..=16;
+
+    pub fn new(
+        width: u32,
+        height: u32,
+        quadrant_width: u32,
+        quadrant_height: u32,
+    ) -> Result<GalaxySize, String> {
+        let (q, s) = (Self::QUADRANTS, Self::SECTORS);
+
+        if !q.contains(&width) || !q.contains(&height) {
+            return Err(format!(
+                "The galaxy must be {} to {} quadrants wide and high, not {width}x{height}",
+                q.start(),
+                q.end()
+            ));
+        }
+
+        if !s.contains(&quadrant_width) || !s.contains(&quadrant_height) {
+            return Err(format!(
+                "Quadrants must be {} to {} sectors wide and high, not {quadrant_width}x{quadrant_height}",
+                s.start(),
+                s.end()
+            ));
+        }
+
+        Ok(GalaxySize {
+            width,
+            height,
+            quadrant_width,
+            quadrant_height,
+        })
+    }
+
+    /* Sectors down and across the whole galaxy */
+    pub fn rows(&self) -> i32 {
+        (self.height * self.quadrant_height) as i32
+    }
+
+    pub fn columns(&self) -> i32 {
+        (self.width * self.quadrant_width) as i32
+    }
+
+    pub fn contains_quadrant(&self, q: &Vec2) -> bool {
+        (0..self.height as i32).contains(&q.x) && (0..self.width as i32).contains(&q.y)
+    }
+
+    pub fn contains_sector(&self, s: &Vec2) -> bool {
+        (0..self.quadrant_height as i32).contains(&s.x)
+            && (0..self.quadrant_width as i32).contains(&s.y)
+    }
+
+    /* Absolute position of a sector of a quadrant */
+    pub fn position(&self, quadrant: &Vec2, sector: &Vec2) -> Vec2 {
+        Vec2 {
+            x: quadrant.x * self.quadrant_height as i32 + sector.x,
+            y: quadrant.y * self.quadrant_width as i32 + sector.y,
+        }
+    }
 }
 
 pub trait SpaceCoordinates<T: HasPosition = Self>: HasPosition {
-    fn get_quadrant_position(&self) -> Vec2 {
+    fn get_quadrant_position(&self, size: GalaxySize) -> Vec2 {
         let p = self.get_position();
         Vec2 {
-            x: p.x / 8,
-            y: p.y / 8,
+            x: p.x.div_euclid(size.quadrant_height as i32),
+            y: p.y.div_euclid(size.quadrant_width as i32),
         }
     }
 
-    fn get_sector_position(&self) -> Vec2 {
+    fn get_sector_position(&self, size: GalaxySize) -> Vec2 {
         let p = self.get_position();
         Vec2 {
-            x: p.x % 8,
-            y: p.y % 8,
+            x: p.x.rem_euclid(size.quadrant_height as i32),
+            y: p.y.rem_euclid(size.quadrant_width as i32),
         }
     }
 
-    fn is_outside(&self) -> bool {
+    fn is_outside(&self, size: GalaxySize) -> bool {
         let p = self.get_position();
-        p.y < 0 || p.y > 63 || p.x < 0 || p.x > 63
+        !(0..size.rows()).contains(&p.x) || !(0..size.columns()).contains(&p.y)
     }
 
     /* Return the distance to an object in x.xx fixed point */
-    fn distance_to(&self, hp: &Box<dyn HasPosition>) -> f32 {
+    fn distance_to(&self, hp: &dyn HasPosition) -> f32 {
         let p1 = self.get_position();
         let p2 = hp.get_position();
         /* We do the squares in fixed point maths */
@@ -143,17 +336,75 @@ pub trait SpaceCoordinates<T: HasPosition = Self>: HasPosition {
         let dsx = dx * dx;
         let dsy = dy * dy;
 
-        let d = ((dsx + dsy) as f32).sqrt();
-
-        return d;
+        ((dsx + dsy) as f32).sqrt()
     }
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
 pub struct Klingon {
     pub position: Vec2,
-    pub energy: u32,
+    pub energy: Energy,
     pub destroyed: bool,
+    #[serde(default)]
+    pub class: KlingonClass,
+}
+
+/*
+ *	The enemy classes of Super Star Trek. Commanders are tougher, hunt
+ *	the Enterprise across the galaxy and can dodge torpedoes, the one
+ *	Super-Commander roams from starbase to starbase destroying them.
+ */
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KlingonClass {
+    #[default]
+    Warship,
+    Commander,
+    SuperCommander,
+}
+
+impl KlingonClass {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KlingonClass::Warship => "Klingon warship",
+            KlingonClass::Commander => "Klingon Commander",
+            KlingonClass::SuperCommander => "Klingon Super-Commander",
+        }
+    }
+
+    pub fn sector_type(&self) -> SectorType {
+        match self {
+            KlingonClass::Warship => SectorType::KLINGON,
+            KlingonClass::Commander => SectorType::COMMANDER,
+            KlingonClass::SuperCommander => SectorType::SUPERCOMMANDER,
+        }
+    }
+
+    /* Times the energy of a warship, and how many warships it counts as
+    in a siege */
+    pub fn strength(&self) -> u32 {
+        match self {
+            KlingonClass::Warship => 1,
+            KlingonClass::Commander => 2,
+            KlingonClass::SuperCommander => 3,
+        }
+    }
+
+    /* Sectors moved per turn in combat on top of the difficulty's */
+    pub fn extra_speed(&self) -> u32 {
+        match self {
+            KlingonClass::Warship => 0,
+            KlingonClass::Commander | KlingonClass::SuperCommander => 1,
+        }
+    }
+
+    /* Chance in 100 to dodge a torpedo */
+    pub fn evasion(&self) -> u32 {
+        match self {
+            KlingonClass::Warship => 0,
+            KlingonClass::Commander => 30,
+            KlingonClass::SuperCommander => 50,
+        }
+    }
 }
 
 impl HasPosition for Klingon {
@@ -170,10 +421,12 @@ impl Moveable for Klingon {}
 
 impl SpaceCoordinates for Klingon {}
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
 pub struct Starbase {
     pub position: Vec2,
     pub destroyed: bool,
+    #[serde(default)]
+    pub siege: Option<f32>, /* Stardate Klingons laying siege to it destroy it */
 }
 
 impl HasPosition for Starbase {
@@ -188,23 +441,35 @@ impl HasPosition for Starbase {
 
 impl SpaceCoordinates for Starbase {}
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Ship {
     pub position: Vec2,
     pub docked: bool,                         /* Docked flag */
     pub torps: u32,                           /* Photon Torpedoes left */
-    pub shield: u32,                          /* Current shield value */
-    pub energy: u32,                          /* Current Energy */
+    pub shield: Energy,                       /* Current shield value */
+    pub energy: Energy,                       /* Current Energy */
     pub devices: HashMap<DeviceType, Device>, //[Device; 8], /* Damage Array */
     pub destroyed: bool,
 }
 
 impl Ship {
     pub fn get_total_energy(&self) -> u32 {
-        self.shield + self.energy
+        self.shield.units().saturating_add(self.energy.units())
     }
 
-    pub fn get_device(&mut self, device_type: DeviceType) -> &Device {
+    /* Move energy between the main supply and the shields so the shields
+    hold `units`, which can't be more than there is in both */
+    pub fn set_shields(&mut self, units: u32) -> Result<(), Insufficient> {
+        let shield = self.shield.units();
+
+        if units > shield {
+            self.energy.transfer_to(&mut self.shield, units - shield)
+        } else {
+            self.shield.transfer_to(&mut self.energy, shield - units)
+        }
+    }
+
+    pub fn get_device(&self, device_type: DeviceType) -> &Device {
         self.devices.get(&device_type).unwrap()
     }
 
@@ -213,13 +478,13 @@ impl Ship {
     }
 
     pub fn is_unable_to_navigate(&self) -> bool {
-        return self.get_total_energy() <= 10
-            && (self.energy < 10
+        self.get_total_energy() <= 10
+            && (self.energy.units() < 10
                 || self
                     .devices
                     .get(&DeviceType::ShieldControl)
                     .unwrap()
-                    .is_damaged());
+                    .is_damaged())
     }
 }
 
@@ -236,7 +501,7 @@ impl HasPosition for Ship {
 impl Moveable for Ship {}
 impl SpaceCoordinates for Ship {}
 
-#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
 pub enum DeviceType {
     #[default]
     WarpEngines,
@@ -251,6 +516,10 @@ pub enum DeviceType {
 }
 
 impl DeviceType {
+    pub fn name(&self) -> &'static str {
+        DEVICE_NAME[*self as usize]
+    }
+
     pub fn from_u8(v: u8) -> DeviceType {
         match v {
             0 => DeviceType::WarpEngines,
@@ -266,9 +535,10 @@ impl DeviceType {
     }
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
 pub struct Device {
     pub damage: u32,
+    #[serde(skip)]
     pub name: &'static str,
     pub device_type: DeviceType,
 }
@@ -283,20 +553,20 @@ impl Device {
     }
 
     pub fn repair_damage(&mut self, damage: u32) {
-        self.damage -= damage;
+        self.damage = self.damage.saturating_sub(damage);
     }
 
     pub fn add_damage(&mut self, damage: u32) {
-        self.damage += damage;
+        self.damage = self.damage.saturating_add(damage);
     }
 
     pub fn is_damaged(&self) -> bool {
         self.damage > 0
     }
 
-    pub fn is_inoperable(&self) -> bool {
+    pub fn is_inoperable(&self, io: &mut dyn Frontend) -> bool {
         if self.is_damaged() {
-            println!(
+            io.println(&format!(
                 "{} {} inoperable.",
                 self.name,
                 if self.device_type == DeviceType::PhotonTubes {
@@ -304,7 +574,7 @@ impl Device {
                 } else {
                     "is"
                 }
-            );
+            ));
 
             return true;
         }
@@ -313,7 +583,7 @@ impl Device {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Quadrant {
     pub position: Vec2,
     pub stars: u32,
@@ -321,6 +591,8 @@ pub struct Quadrant {
     pub starbases: u32,
     pub visited: bool,
     pub name: String,
+    #[serde(default)]
+    pub record: Option<String>, /* Scan code when last seen, None if never */
 }
 
 impl Default for Quadrant {
@@ -332,18 +604,46 @@ impl Default for Quadrant {
             starbases: 0,
             name: "".to_string(),
             visited: false,
+            record: None,
         }
     }
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+impl Quadrant {
+    /* Klingons, starbases and stars as the long range sensors show them */
+    pub fn scan_code(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.klingons & 0x0F,
+            self.starbases & 0x0F,
+            self.stars & 0x0F
+        )
+    }
+
+    /* Seen by the sensors, the computer record holds what they saw until
+    the next look */
+    pub fn scan(&mut self) {
+        self.visited = true;
+        self.record = Some(self.scan_code());
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
 pub struct Sector {
     pub position: Vec2,
     pub sector_type: SectorType,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Game {
+    #[serde(skip)]
     pub exit_flag: bool,
+    #[serde(default)]
+    pub game_over: bool, /* Mission won or lost */
+    pub seed: u64, /* Seed the galaxy was built from */
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    pub rng: GameRng, /* Every random decision of the game comes from here */
     pub starbases: Vec<Starbase>,
     pub klingons: Vec<Klingon>,
     pub ship: Ship,
@@ -359,31 +659,37 @@ pub struct Game {
     pub time_start: f32, /* Starting Stardate */
     pub time_up: f32,    /* End of time */
     pub stardate: f32,   /* Current Stardate */
+    #[serde(default)]
+    pub fleet_stardate: f32, /* When the Klingon fleet last moved */
     pub d4: i32,
-    pub quadrant_map: [[Quadrant; 8]; 8], /* Galaxy. BCD of k b s plus flag */
-    pub sector_map: [[Sector; 64]; 64],
+    #[serde(default)]
+    pub energy_used: u32, /* Spent on moving and weapons, for the score */
+    #[serde(default)]
+    pub size: GalaxySize,
+    pub quadrant_map: Vec<Vec<Quadrant>>, /* Galaxy, [x][y] */
+    pub sector_map: Vec<Vec<Sector>>,     /* Every sector of the galaxy, [x][y] */
 }
 
 impl Game {
     pub fn get_klingons_idxs_in_current_quadrant(&mut self) -> Vec<usize> {
-        let ship_quadrant_position = self.ship.get_quadrant_position();
+        let ship_quadrant_position = self.ship.get_quadrant_position(self.size);
 
         self.klingons
             .iter()
             .enumerate()
             .filter(|(_, k)| !k.destroyed)
-            .filter(|(_, k)| k.get_quadrant_position() == ship_quadrant_position)
+            .filter(|(_, k)| k.get_quadrant_position(self.size) == ship_quadrant_position)
             .map(|(idx, _)| idx)
             .collect::<Vec<_>>()
     }
 
-    pub fn no_klingons_in_current_quadrant(&self) -> bool {
+    pub fn no_klingons_in_current_quadrant(&self, io: &mut dyn Frontend) -> bool {
         let current_quadrant = self.get_current_quadrant();
 
         if current_quadrant.klingons == 0 {
-            println!(
+            io.println(
                 "Science Officer Spock reports:
-            'Sensors show no enemy ships in this quadrant'"
+            'Sensors show no enemy ships in this quadrant'",
             );
 
             return true;
@@ -392,6 +698,18 @@ impl Game {
         false
     }
 
+    pub fn condition(&self) -> &'static str {
+        if self.ship.docked {
+            "DOCKED"
+        } else if self.get_current_quadrant().klingons > 0 {
+            "*RED*"
+        } else if self.ship.energy.units() < STARTING_ENERGY / 10 {
+            "YELLOW"
+        } else {
+            "GREEN"
+        }
+    }
+
     pub fn get_sector(&self, position: &Vec2) -> &Sector {
         &self.sector_map[position.x as usize][position.y as usize]
     }
@@ -401,137 +719,59 @@ impl Game {
     }
 
     pub fn get_current_quadrant(&self) -> &Quadrant {
-        let ship_quadrant_position = self.ship.get_quadrant_position();
+        let ship_quadrant_position = self.ship.get_quadrant_position(self.size);
 
         &self.quadrant_map[ship_quadrant_position.x as usize][ship_quadrant_position.y as usize]
     }
 
     pub fn get_mut_current_quadrant(&mut self) -> &mut Quadrant {
-        let current_quadrant_position = self.ship.get_quadrant_position();
+        let current_quadrant_position = self.ship.get_quadrant_position(self.size);
 
         &mut self.quadrant_map[current_quadrant_position.x as usize]
             [current_quadrant_position.y as usize]
     }
 
-    pub fn initialize() -> Game {
-        /* Seed the randomizer with the timer */
-        // srand((unsigned) time(NULL));
+    pub fn initialize(seed: u64, difficulty: Difficulty, size: GalaxySize) -> Game {
+        /* Seed the randomizer, the same seed always gives the same game */
+        let mut rng = new_rng(seed);
 
         /* Max of 4000, which works nicely with our 0.1 fixed point giving
         us a 16bit unsigned range of time */
-        let stardate = (get_randf32() * 2000.0) + 2000.0;
+        let stardate = (get_randf32(&mut rng) * 2000.0) + 2000.0;
 
-        let mut sector_map = [[Sector::default(); 64]; 64];
-        let mut quadrant_map: [[Quadrant; 8]; 8] = [
-            [
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-            ],
-            [
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-            ],
-            [
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-            ],
-            [
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-            ],
-            [
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-            ],
-            [
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-            ],
-            [
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-            ],
-            [
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-                Quadrant::default(),
-            ],
-        ];
+        let mut sector_map =
+            vec![vec![Sector::default(); size.columns() as usize]; size.rows() as usize];
+        let mut quadrant_map: Vec<Vec<Quadrant>> = (0..size.height)
+            .map(|_| (0..size.width).map(|_| Quadrant::default()).collect())
+            .collect();
 
         /* Initialize time */
-        let mut time_days = 25i32 + (get_randf32() * 10.0) as i32;
+        let mut time_days =
+            difficulty.min_days + (get_randf32(&mut rng) * difficulty.extra_days as f32) as i32;
 
         /* Initialize Enterprise */
-        let ship_position = find_empty_place_in_quadrant(
-            &sector_map,
-            &Vec2 {
-                x: get_rand8(),
-                y: get_rand8(),
-            },
-        );
+        let ship_quadrant_position = Vec2 {
+            x: get_rand(&mut rng, size.height as i32) - 1,
+            y: get_rand(&mut rng, size.width as i32) - 1,
+        };
+        let ship_position =
+            find_empty_place_in_quadrant(&mut rng, size, &sector_map, &ship_quadrant_position);
 
         sector_map[ship_position.x as usize][ship_position.y as usize].sector_type =
             SectorType::SHIP;
 
-        let energy = STARTING_ENERGY;
+        let energy = Energy::new(STARTING_ENERGY);
         let torps = DEFAULT_PHOTON_TORPEDO_CAPACITY;
-        let shield = 0;
+        let shield = Energy::default();
 
         let mut devices = HashMap::new(); //[Device::default(); DeviceType::DeviceNum as usize]; // (1..=8)
 
-        for i in 0..DeviceType::DeviceNum as usize {
+        for (i, name) in DEVICE_NAME.iter().enumerate() {
             let device_type = DeviceType::from_u8(i as u8);
             let device = Device {
                 damage: 0,
-                device_type: DeviceType::from_u8(i as u8),
-                name: &DEVICE_NAME[i as usize],
+                device_type,
+                name,
             };
 
             devices.insert(device_type, device);
@@ -539,44 +779,39 @@ impl Game {
 
         /* Setup What Exists in Galaxy */
         let mut klingons_in_quadrant: u32;
-        let mut klingons_left: u32 = 0;
         let mut total_klingons: u32 = 0;
 
         let mut starbases_in_quadrant: u32;
-        let mut starbases_left: u32 = 0;
         let mut total_starbases: u32 = 0;
 
         let mut klingons: Vec<Klingon> = Vec::new();
         let mut starbases: Vec<Starbase> = Vec::new();
 
-        for i in 0..8 {
-            for j in 0..8 {
+        for i in 0..size.height as i32 {
+            for j in 0..size.width as i32 {
                 let quadrant_position = Vec2 { x: i, y: j };
-                let r: u8 = get_rand(100i32) as u8;
-
-                klingons_in_quadrant = 0;
+                let r: u8 = get_rand(&mut rng, 100) as u8;
 
-                if r > 98 {
-                    klingons_in_quadrant = 3;
-                } else if r > 95 {
-                    klingons_in_quadrant = 2;
-                } else if r > 80 {
-                    klingons_in_quadrant = 1
-                }
+                klingons_in_quadrant = difficulty.klingons_for_roll(r);
 
-                total_klingons = total_klingons + klingons_in_quadrant;
+                total_klingons += klingons_in_quadrant;
 
                 for _ in 0..klingons_in_quadrant {
-                    let klingon_position =
-                        find_empty_place_in_quadrant(&sector_map, &quadrant_position);
+                    let klingon_position = find_empty_place_in_quadrant(
+                        &mut rng,
+                        size,
+                        &sector_map,
+                        &quadrant_position,
+                    );
 
                     sector_map[klingon_position.x as usize][klingon_position.y as usize]
                         .sector_type = SectorType::KLINGON;
 
                     let klingon = Klingon {
                         position: klingon_position,
-                        energy: STARTING_ENERGY,
+                        energy: Energy::new(difficulty.klingon_energy),
                         destroyed: false,
+                        class: KlingonClass::Warship,
                     };
 
                     klingons.push(klingon);
@@ -584,17 +819,21 @@ impl Game {
 
                 starbases_in_quadrant = 0;
 
-                let r: u8 = get_rand(100i32) as u8;
+                let r: u8 = get_rand(&mut rng, 100) as u8;
 
-                if r > 96 {
+                if r > difficulty.starbase_threshold {
                     starbases_in_quadrant = 1;
                 }
 
-                starbases_left = starbases_left + starbases_in_quadrant;
+                total_starbases += starbases_in_quadrant;
 
                 for _ in 0..starbases_in_quadrant {
-                    let starbase_position =
-                        find_empty_place_in_quadrant(&sector_map, &quadrant_position);
+                    let starbase_position = find_empty_place_in_quadrant(
+                        &mut rng,
+                        size,
+                        &sector_map,
+                        &quadrant_position,
+                    );
 
                     sector_map[starbase_position.x as usize][starbase_position.y as usize]
                         .sector_type = SectorType::BASE;
@@ -602,22 +841,27 @@ impl Game {
                     let starbase = Starbase {
                         position: starbase_position,
                         destroyed: false,
+                        siege: None,
                     };
 
                     starbases.push(starbase);
                 }
 
-                let stars_in_quadrant: u32 = rand8() as u32;
+                let stars_in_quadrant: u32 = rand8(&mut rng) as u32;
 
                 for _k in 0..stars_in_quadrant {
-                    let star_position =
-                        find_empty_place_in_quadrant(&sector_map, &quadrant_position);
+                    let star_position = find_empty_place_in_quadrant(
+                        &mut rng,
+                        size,
+                        &sector_map,
+                        &quadrant_position,
+                    );
 
                     sector_map[star_position.x as usize][star_position.y as usize].sector_type =
                         SectorType::STAR;
                 }
 
-                let quadrant_name = get_quadrant_name(&quadrant_position, true);
+                let quadrant_name = get_quadrant_name(size, &quadrant_position, true);
                 quadrant_map[i as usize][j as usize] = Quadrant {
                     visited: false,
                     klingons: klingons_in_quadrant,
@@ -625,32 +869,72 @@ impl Game {
                     stars: stars_in_quadrant,
                     position: quadrant_position,
                     name: quadrant_name,
+                    record: None,
                 }
             }
         }
 
         /* Give more time for more Klingons */
-        if klingons_left as i32 > time_days {
-            time_days = klingons_left as i32 + 1;
+        if total_klingons as i32 > time_days {
+            time_days = total_klingons as i32 + 1;
         }
 
         if total_starbases == 0 {
             let starbase_quadrant_position = Vec2 {
-                x: get_rand8(),
-                y: get_rand8(),
+                x: get_rand(&mut rng, size.height as i32) - 1,
+                y: get_rand(&mut rng, size.width as i32) - 1,
             };
 
-            let starbase_sector_position =
-                find_empty_place_in_quadrant(&sector_map, &starbase_quadrant_position);
+            let starbase_sector_position = find_empty_place_in_quadrant(
+                &mut rng,
+                size,
+                &sector_map,
+                &starbase_quadrant_position,
+            );
 
             sector_map[starbase_sector_position.x as usize][starbase_sector_position.y as usize]
                 .sector_type = SectorType::BASE;
 
+            starbases.push(Starbase {
+                position: starbase_sector_position,
+                destroyed: false,
+                siege: None,
+            });
+
+            quadrant_map[starbase_quadrant_position.x as usize]
+                [starbase_quadrant_position.y as usize]
+                .starbases = 1;
+
             total_starbases = 1;
         }
 
-        starbases_left = total_starbases;
-        klingons_left = total_klingons;
+        /* Some Klingons are promoted, the Super-Commander last */
+        let ranks = std::iter::repeat_n(KlingonClass::Commander, difficulty.commanders as usize)
+            .chain(
+                difficulty
+                    .super_commander
+                    .then_some(KlingonClass::SuperCommander),
+            );
+
+        for class in ranks {
+            let warships: Vec<usize> = (0..klingons.len())
+                .filter(|i| klingons[*i].class == KlingonClass::Warship)
+                .collect();
+
+            if warships.is_empty() {
+                break;
+            }
+
+            let k = &mut klingons[warships[get_rand(&mut rng, warships.len() as i32) as usize - 1]];
+
+            k.class = class;
+            k.energy = Energy::new(difficulty.klingon_energy * class.strength());
+            sector_map[k.position.x as usize][k.position.y as usize].sector_type =
+                class.sector_type();
+        }
+
+        let starbases_left = total_starbases;
+        let klingons_left = total_klingons;
 
         let ship = Ship {
             position: ship_position,
@@ -668,13 +952,20 @@ impl Game {
         let time_up = time_start + time_days as f32;
 
         Game {
+            difficulty,
             d4: 0,
+            energy_used: 0,
             exit_flag: false,
+            game_over: false,
+
+            seed,
+            rng,
 
             starbases,       /* Starbase Location in sector */
             starbases_left,  /* Total Starbases left */
             total_starbases, /* Total Starbases at start */
 
+            size,
             quadrant_map, /* Galaxy. BCD of k b s plus flag */
 
             klingons,       /* Klingons at start */
@@ -686,100 +977,49 @@ impl Game {
             time_start, /* Starting Stardate */
             time_up,    /* End of time */
             stardate,   /* Current Stardate */
+            fleet_stardate: stardate,
 
             sector_map,
         }
     }
 }
 
-pub fn get_course(officer: &str) -> Option<f32> {
-    //print!("Course (0-9): "); // 0? -> does not move
-    //let mut course = input_f32();
-
-    let mut course = input_f32("Course (0-9): ", 0.0, 1000.0);
-
-    if course == 9.0 {
-        course = 1.0;
-    }
-
-    if course < 1.0 || course >= 9.0 {
-        println!("{officer} {INC_1}");
-        return None;
-    }
-
-    Some(course)
-}
-
-pub fn get_new_position(c1: f32) -> Vec2 {
-    let x = match c1 as i32 {
-        1 => 1,
-        2 => 1,
-        3 => 0,
-        4 => -1,
-        5 => -1,
-        6 => -1,
-        7 => 0,
-        8 => 1,
-        _ => 0,
-    };
-
-    let y = match c1 as i32 {
-        1 => 0,
-        2 => 1,
-        3 => 1,
-        4 => 1,
-        5 => 0,
-        6 => -1,
-        7 => -1,
-        8 => -1,
-        _ => 0,
-    };
+/* Course 9 is the same as 1, None if the player cancels */
+pub fn get_course(io: &mut dyn Frontend, officer: &str) -> Option<f32> {
+    let course = ask_f32(io, "Course (0-9): ", 0.0, 9.0, |course| {
+        if course < 1.0 {
+            Err(format!("{officer} {INC_1}"))
+        } else {
+            Ok(())
+        }
+    })?;
 
-    Vec2 {
-        x: x as i32,
-        y: y as i32,
-    }
+    Some(if course == 9.0 { 1.0 } else { course })
 }
 
-pub fn klingons_move(game: &mut Game) {
-    //println!("klingons_move");
-    let ship_quadrant_position = game.ship.get_quadrant_position();
-
-    let alive_local_klingons = game.get_klingons_idxs_in_current_quadrant();
-
-    for idx in alive_local_klingons {
-        let k = game.klingons.get_mut(idx).unwrap();
-
-        let old_klingon_position = k.get_position().clone();
-
-        game.sector_map[old_klingon_position.x as usize][old_klingon_position.y as usize]
-            .sector_type = SectorType::SPACE;
+/* Every Klingon in the quadrant moves with intent before they open fire */
+pub fn klingons_move(game: &mut Game, io: &mut dyn Frontend) {
+    let mut taken = Vec::new();
 
-        let new_klingon_position =
-            find_empty_place_in_quadrant(&game.sector_map, &ship_quadrant_position);
-
-        k.move_to(&new_klingon_position);
-
-        game.sector_map[k.position.x as usize][k.position.y as usize].sector_type =
-            SectorType::KLINGON;
+    for idx in game.get_klingons_idxs_in_current_quadrant() {
+        tactical_move(game, idx, &mut taken);
     }
 
-    klingons_shoot(game);
+    klingons_shoot(game, io);
 }
 
-pub fn klingons_shoot(game: &mut Game) {
-    println!("klingons_shoot");
-    let ship_quadrant_position = game.ship.get_quadrant_position();
+pub fn klingons_shoot(game: &mut Game, io: &mut dyn Frontend) {
+    let ship_quadrant_position = game.ship.get_quadrant_position(game.size);
 
     if game.quadrant_map[ship_quadrant_position.x as usize][ship_quadrant_position.y as usize]
         .klingons
-        <= 0
+        == 0
     {
         return;
     }
 
     if game.ship.docked {
-        println!("Starbase shields protect the Enterprise");
+        io.println("Starbase shields protect the Enterprise");
         return;
     }
 
@@ -790,72 +1030,72 @@ pub fn klingons_shoot(game: &mut Game) {
     for idx in alive_local_klingons {
         let k = game.klingons.get_mut(idx).unwrap();
 
-        let klingon_sector_position = k.get_sector_position();
+        let klingon_sector_position = k.get_sector_position(game.size);
 
-        // energy  + 200-300
-        let mut h = k.energy * (200 + get_rand(100)) as u32;
+        let mut h = k.energy.units()
+            * (game.difficulty.hit_base
+                + get_rand(&mut game.rng, game.difficulty.hit_spread as i32) as u32);
 
-        let hp: Box<dyn HasPosition> = Box::new(*k);
-        let d = game.ship.distance_to(&hp);
+        let d = game.ship.distance_to(k);
 
-        println!("distance: {}", d);
         h = (h as f32 / d) as u32;
 
         h /= 1000;
 
-        if h > game.ship.shield {
+        /* A hit the shields can't take destroys the ship */
+        if game.ship.shield.take(h).is_err() {
+            game.ship.shield.drain(h);
             ship_is_destroyed = true;
         }
 
-        game.ship.shield = game.ship.shield - h;
-
-        k.energy = (k.energy * 100) / (300 + get_rand(100)) as u32;
+        k.energy =
+            Energy::new((k.energy.units() * 100) / (300 + get_rand(&mut game.rng, 100)) as u32);
 
-        println!(
-            "{} unit hit on Enterprise from sector {}, {}",
-            h, klingon_sector_position.x, klingon_sector_position.y
-        );
+        io.event(Event::ShipHit {
+            from: klingon_sector_position,
+            damage: h,
+        });
 
         if ship_is_destroyed {
             break;
         }
 
-        println!("    <Shields down to {} units>\n", game.ship.shield);
+        io.event(Event::ShieldsDown {
+            shields: game.ship.shield.units(),
+        });
 
         if h >= 20 {
             /* The check in basic is float and is h/s >.02. We
             have to use 32bit values here to avoid an overflow
             FIXME: use a better algorithm perhaps ? */
 
-            let ratio = h / game.ship.shield;
+            let ratio = h / game.ship.shield.units().max(1);
 
-            if get_rand(10) <= 6 || ratio > 2 {
-                let r = rand8() as u8;
+            if get_rand(&mut game.rng, 10) <= 6 || ratio > 2 {
+                let r = get_rand8(&mut game.rng) as u8;
 
                 let device = game.ship.get_mut_device(DeviceType::from_u8(r));
                 /* The original basic code computed h/s in
                 float form the C conversion broke this. We correct it in the fixed
                 point change */
-                device.add_damage((ratio + get_rand(50) as u32) as u32);
-
-                /* FIXME: can we use dcr_1 here ?? */
-                println!(
-                    "Damage Control reports\n   '{}' damaged by hit\n",
-                    device.name
-                );
+                let damage = ratio + get_rand(&mut game.rng, 50) as u32;
+                device.add_damage(damage);
+
+                io.event(Event::DeviceDamaged {
+                    device: device.device_type,
+                    damage,
+                    by_hit: true,
+                });
             }
         }
     }
 
     if ship_is_destroyed {
-        println!("");
-        ship_destroyed(game);
+        ship_destroyed(game, io);
     }
 }
 
-pub fn repair_damage(game: &mut Game, warp: f32) {
-    let mut d1: i32 = 0;
-
+pub fn repair_damage(game: &mut Game, io: &mut dyn Frontend, warp: f32) {
     let mut repair_factor: u32 = (warp * 100.0) as u32;
 
     if warp >= 100.0 {
@@ -874,239 +1114,232 @@ pub fn repair_damage(game: &mut Game, warp: f32) {
                 /* -0.1 */
                 device.set_damage(10);
             } else if !device.is_damaged() {
-                if d1 != 1 {
-                    d1 = 1;
-                    print!("{}", DCR_1);
-                }
-                println!("    {} repair completed\n", device.name);
+                io.event(Event::DeviceRepaired {
+                    device: device.device_type,
+                });
             }
         }
     }
 
-    if get_rand(10) <= 2 {
-        let r = rand8() as u8;
+    if get_rand(&mut game.rng, 10) <= 2 {
+        let r = get_rand8(&mut game.rng) as u8;
         let device = game.ship.get_mut_device(DeviceType::from_u8(r));
 
-        if get_rand(10) < 6 {
+        if get_rand(&mut game.rng, 10) < 6 {
             /* Working in 1/100ths */
-            device.add_damage((get_rand(500) + 100) as u32);
-            print!("{}", DCR_1);
-            println!("    {} damaged\n", device.name);
+            let damage = (get_rand(&mut game.rng, 500) + 100) as u32;
+            device.add_damage(damage);
+            io.event(Event::DeviceDamaged {
+                device: device.device_type,
+                damage,
+                by_hit: false,
+            });
         } else {
             /* Working in 1/100ths */
-            device.repair_damage((get_rand(300) + 100) as u32);
-            print!("{}", DCR_1);
-            println!("    {} state of repair improved\n", device.name);
+            device.repair_damage((get_rand(&mut game.rng, 300) + 100) as u32);
+            io.event(Event::DeviceRepairImproved {
+                device: device.device_type,
+            });
         }
     }
 }
 
 pub fn find_empty_place_in_quadrant(
-    sector_map: &[[Sector; 64]; 64],
+    rng: &mut GameRng,
+    size: GalaxySize,
+    sector_map: &[Vec<Sector>],
     quadrant_position: &Vec2,
 ) -> Vec2 {
-    let mut x: i32;
-    let mut y: i32;
-
     loop {
-        x = (quadrant_position.x * 8 + get_rand8()) as i32;
-        y = (quadrant_position.y * 8 + get_rand8()) as i32;
+        let sector_position = Vec2 {
+            x: get_rand(rng, size.quadrant_height as i32) - 1,
+            y: get_rand(rng, size.quadrant_width as i32) - 1,
+        };
+        let p = size.position(quadrant_position, &sector_position);
 
-        if !(sector_map[x as usize][y as usize].sector_type != SectorType::SPACE) {
-            break;
+        if sector_map[p.x as usize][p.y as usize].sector_type == SectorType::SPACE {
+            return p;
         }
     }
-
-    Vec2 { x, y }
 }
 
-pub fn get_quadrant_name(p: &Vec2, small: bool) -> String {
+pub fn get_quadrant_name(size: GalaxySize, p: &Vec2, small: bool) -> String {
+    if !size.contains_quadrant(p) {
+        return "Unknown".to_string();
+    }
+
     let x = p.x as usize;
     let y = p.y as usize;
 
-    if p.is_outside() {
-        return "Unknown".to_string();
-    }
+    /* Every row of the galaxy holds two regions, taking half of it each.
+    Larger galaxies reuse the names of the upper rows */
+    let half = (size.width as usize / 2).max(1);
 
-    let quadname = if x <= 4 {
-        QUADRANT_NAME[y]
+    let (quadname, column) = if y < half {
+        (QUADRANT_NAME[x % 8], y)
     } else {
-        QUADRANT_NAME[y + 8]
+        (QUADRANT_NAME[x % 8 + 8], y - half)
     };
 
-    let sectorname = if small { SECTOR_NAME[x] } else { "" };
+    let sectorname = if small {
+        SECTOR_NAME[column % SECTOR_NAME.len()]
+    } else {
+        ""
+    };
 
     format!("{}{}", quadname, sectorname)
 }
 
-pub fn ship_destroyed(game: &mut Game) {
-    println!(
-        "The Enterprise has been destroyed.
-    The Federation will be conquered.\n"
-    );
-
-    end_of_time(game);
+pub fn ship_destroyed(game: &mut Game, io: &mut dyn Frontend) {
+    lose_game(game, io, LossReason::ShipDestroyed);
 }
 
-pub fn end_of_time(game: &mut Game) {
-    println!("It is stardate {:.2}.\n", game.stardate);
-
-    resign_commision(game);
+pub fn end_of_time(game: &mut Game, io: &mut dyn Frontend) {
+    lose_game(game, io, LossReason::TimeExpired);
 }
 
-pub fn resign_commision(game: &mut Game) {
-    println!(
-        "There were {} Klingon Battlecruisers left at the
- end of your mission.\n",
-        game.klingons_left
-    );
-
-    end_of_game(game);
+pub fn resign_commision(game: &mut Game, io: &mut dyn Frontend) {
+    lose_game(game, io, LossReason::Resigned);
 }
 
-pub fn won_game(game: &mut Game) {
-    println!(
-        "Congratulations, Captain!  The last Klingon Battle Cruiser
- menacing the Federation has been destroyed."
-    );
-    /*
-    if game.stardate - game.time_start > 0.0 {
-        let n = game.total_klingons as f32;
-        let d = game.stardate - game.time_start;
-        let v = square00(n as i32 / d as i32);
-
-        println!("Your efficiency rating is {}\n", print100(v));
-        // 1000 * pow(total_klingons / (float)(FROM_FIXED(t) - time_start), 2));
+pub fn lose_game(game: &mut Game, io: &mut dyn Frontend, reason: LossReason) {
+    if game.game_over {
+        return;
     }
-    */
-    end_of_game(game);
-}
 
-pub fn end_of_game(game: &mut Game) {
-    game.exit_flag = true;
+    io.event(Event::GameLost {
+        reason,
+        stardate: game.stardate,
+        klingons_left: game.klingons_left,
+    });
 
-    if game.starbases_left > 0 {
-        /* FIXME: showfile ? */
-        println!(
-            "The Federation is in need of a new starship commander
- for a similar mission. "
-        );
+    let score = Score::new(game, reason != LossReason::ShipDestroyed);
 
-        let x = get_command("If there is a volunteer, let him step forward and enter 'aye'");
+    io.event(Event::FinalScore(score.clone()));
 
-        game.exit_flag = x != "aye";
-    }
+    end_of_game(game, io, &score, Some(reason));
 }
 
-const INC_1: &str = "reports:\n  Incorrect course data, sir!";
+pub fn won_game(game: &mut Game, io: &mut dyn Frontend) {
+    if game.game_over {
+        return;
+    }
 
-pub fn course_control(game: &mut Game) {
-    let mut warpmax: f32 = 8.0;
+    io.event(Event::GameWon);
+    let score = Score::new(game, true);
 
-    let c1 = get_course("Lt. Sulu");
+    io.event(Event::FinalScore(score.clone()));
+
+    end_of_game(game, io, &score, None);
+}
 
-    if c1 == None {
+/* None as the reason means the mission was won */
+pub fn end_of_game(
+    game: &mut Game,
+    io: &mut dyn Frontend,
+    score: &Score,
+    reason: Option<LossReason>,
+) {
+    /* The mission can only end once */
+    if game.game_over {
         return;
     }
 
-    let c1 = c1.unwrap();
+    game.game_over = true;
+    game.exit_flag = true;
 
-    if game.ship.get_device(DeviceType::WarpEngines).is_damaged() {
-        warpmax = 0.2;
-    }
+    io.mission_over(game, score, reason);
 
-    //println!("Warp Factor (0-{}): ", warpmax);
+    if game.starbases_left > 0 {
+        /* FIXME: showfile ? */
+        io.println(
+            "The Federation is in need of a new starship commander
+ for a similar mission. ",
+        );
 
-    let warp = input_f32(
-        format!("Warp Factor (0-{}): ", warpmax).as_str(),
-        0.0,
-        warpmax,
-    );
-    //println!("warp: {:.2}", warp);
+        let x = io.get_command("If there is a volunteer, let him step forward and enter 'aye'");
 
-    if game.ship.get_device(DeviceType::WarpEngines).is_damaged() && warp > 0.2 {
-        println!(
-            "Warp Engines are damaged.
-Maximum speed = Warp 0.2\n"
-        );
-        return;
+        game.exit_flag = x.trim() != "aye";
     }
+}
 
-    if warp <= 0.0 {
-        return;
-    }
+/* Energy to travel at a warp factor, a unit a sector to the nearest */
+pub fn warp_energy(warp: f32, size: GalaxySize) -> u32 {
+    (((warp * size.quadrant_width as f32 * 100.0) + 50.0) / 100.0) as u32
+}
 
-    if warp > 8.0 {
-        println!(
-            "Chief Engineer Scott reports:\n
-  The engines won't take warp {:.2}!",
-            warp
-        );
-        return;
-    }
+const INC_1: &str = "reports:\n  Incorrect course data, sir!";
 
-    //println!("Warp: {warp}");
-    //println!("Warp: {warp:.2}");
+pub fn course_control(game: &mut Game, io: &mut dyn Frontend) {
+    let Some(c1) = get_course(io, "Lt. Sulu") else {
+        return;
+    };
 
-    //println!("Energy needed: {:.2}", warp * 8.0);
-    //n = (warp * 8.0) as i32;
-    //println!("Energy needed {n}");
-    //n = (n + 50) / 100; // energy needed
-    //println!("Energy needed {n}");
+    let engines_damaged = game.ship.get_device(DeviceType::WarpEngines).is_damaged();
+    let shields_usable = !game.ship.get_device(DeviceType::ShieldControl).is_damaged();
+    let size = game.size;
+    let (energy, shield) = (game.ship.energy.units(), game.ship.shield.units());
+    let energy_for = |warp: f32| warp_energy(warp, size);
 
-    let n = (((warp * 800.0) + 50.0) / 100.0) as u32; // rounded up energy needed
+    let warpmax = if engines_damaged { 0.2 } else { 8.0 };
 
-    println!("Energy needed {n}");
+    let warp = ask_f32(
+        io,
+        &format!("Warp Factor (0-{}): ", warpmax),
+        0.0,
+        8.0,
+        |warp| {
+            if engines_damaged && warp > 0.2 {
+                return Err("Warp Engines are damaged.\nMaximum speed = Warp 0.2\n".to_string());
+            }
 
-    /* FIXME: should be  s + e - n > 0 iff shield control undamaged */
-    if game.ship.energy < n {
-        println!(
-            "Engineering reports:
+            /* FIXME: should be  s + e - n > 0 iff shield control undamaged */
+            if energy < energy_for(warp) {
+                let mut reason = format!(
+                    "Engineering reports:
   Insufficient energy available for maneuvering
- at warp {:.2}!\n",
-            warp
-        );
+ at warp {:.2}, only {} units available!\n",
+                    warp, energy
+                );
 
-        if game.ship.shield >= n && !game.ship.get_device(DeviceType::ShieldControl).is_damaged() {
-            println!(
-                "Deflector Control Room acknowledges:
+                if shield >= energy_for(warp) && shields_usable {
+                    reason.push_str(&format!(
+                        "Deflector Control Room acknowledges:
   {} units of energy presently deployed to shields.",
-                game.ship.shield
-            );
-        }
-
-        return;
-    }
+                        shield
+                    ));
+                }
 
-    klingons_move(game);
+                return Err(reason);
+            }
 
-    repair_damage(game, warp);
+            Ok(())
+        },
+    );
 
-    let c1_position = get_new_position(c1);
+    let Some(warp) = warp else {
+        return;
+    };
 
-    // depends on c1
+    if warp <= 0.0 {
+        return;
+    }
 
-    println!(
-        "c1 {c1}, c1_position_: {}, {}",
-        c1_position.x, c1_position.y
-    );
+    let n = energy_for(warp);
 
-    // we advance n times: 0-64
-    for i in 0..n {
-        println!("i: {}", i);
-        let mut can_move = true;
+    io.println(&format!("Energy needed {n}"));
 
-        let mut ship_new_position = game.ship.position.clone();
+    klingons_move(game, io);
 
-        ship_new_position.x += c1_position.x;
-        ship_new_position.y += c1_position.y;
+    repair_damage(game, io, warp);
 
-        if ship_new_position.is_outside() {
-            let ship_sector_position = ship_new_position.get_sector_position();
-            let ship_quadrant_position = ship_new_position.get_quadrant_position();
+    for ship_new_position in Track::new(&game.ship.position, c1).take(n as usize) {
+        let ship_sector_position = game.ship.get_sector_position(game.size);
+        let ship_quadrant_position = game.ship.get_quadrant_position(game.size);
 
+        if ship_new_position.is_outside(game.size) {
             /* Mostly showfile ? FIXME */
-            println!(
+            io.println(&format!(
                 "LT. Uhura reports:
   Message from Starfleet Command:
 
@@ -1115,60 +1348,50 @@ Maximum speed = Warp 0.2\n"
 
 Chief Engineer Scott reports:
   Warp Engines shut down at sector {}, {} of quadrant {}, {}.\n",
-                ship_sector_position.x,
-                ship_sector_position.y,
-                ship_quadrant_position.x,
-                ship_quadrant_position.y
-            );
+                ship_sector_position.x + 1,
+                ship_sector_position.y + 1,
+                ship_quadrant_position.x + 1,
+                ship_quadrant_position.y + 1
+            ));
 
-            can_move = false;
-        } else if game.sector_map[ship_new_position.x as usize][ship_new_position.x as usize]
-            .sector_type
-            != SectorType::SPACE
-        {
-            let ship_sector_position = ship_new_position.get_sector_position();
+            break;
+        }
 
-            println!(
+        if game.get_sector(&ship_new_position).sector_type != SectorType::SPACE {
+            io.println(&format!(
                 "Warp Engines shut down at sector
 {}, {} due to bad navigation.\n",
-                ship_sector_position.x, ship_sector_position.y
-            );
+                ship_sector_position.x + 1,
+                ship_sector_position.y + 1
+            ));
 
-            can_move = false;
+            break;
         }
 
-        maneuver_energy(game, n);
+        let ship_old_position = game.ship.position;
 
-        if game.stardate > game.time_up {
-            end_of_time(game);
-        }
+        game.get_mut_sector(&ship_old_position).sector_type = SectorType::SPACE;
+        game.ship.position = ship_new_position;
+        game.get_mut_sector(&ship_new_position).sector_type = SectorType::SHIP;
 
-        if can_move {
-            let ship_old_quadrant_position = game.ship.get_quadrant_position();
-            let ship_new_quadrant_position = ship_new_position.get_quadrant_position();
-            game.sector_map[game.ship.position.x as usize][game.ship.position.y as usize]
-                .sector_type = SectorType::SPACE;
-            game.ship.position = ship_new_position;
-            game.sector_map[game.ship.position.x as usize][game.ship.position.y as usize]
-                .sector_type = SectorType::SHIP;
+        game.stardate += 0.1;
 
-            game.stardate += 0.1;
+        if game.stardate > game.time_up {
+            return end_of_time(game, io);
+        }
 
-            if ship_new_quadrant_position != ship_old_quadrant_position {
-                new_quadrant(game);
-            }
-        } else {
-            break;
+        if ship_new_position.get_quadrant_position(game.size) != ship_quadrant_position {
+            new_quadrant(game, io);
         }
     }
 
-    complete_maneuver(game, warp as i32, n as u32);
+    complete_maneuver(game, io, warp as i32, n);
 }
 
-pub fn complete_maneuver(game: &mut Game, warp: i32, n: u32) {
+pub fn complete_maneuver(game: &mut Game, io: &mut dyn Frontend, warp: i32, n: u32) {
     let mut time_used: i32;
 
-    maneuver_energy(game, n);
+    maneuver_energy(game, io, n);
 
     time_used = 10;
 
@@ -1179,174 +1402,173 @@ pub fn complete_maneuver(game: &mut Game, warp: i32, n: u32) {
     game.stardate += time_used as f32;
 
     if game.stardate > game.time_up {
-        return end_of_time(game);
+        return end_of_time(game, io);
     }
 
-    short_range_scan(game);
+    short_range_scan(game, io);
 }
 
-pub fn maneuver_energy(game: &mut Game, n: u32) {
+pub fn maneuver_energy(game: &mut Game, io: &mut dyn Frontend, n: u32) {
     let energy_needed = n + 10;
-    let enough_energy = game.ship.energy >= energy_needed;
 
-    if enough_energy {
-        game.ship.energy -= energy_needed;
-        return;
-    }
-
-    /*
-    /* FIXME:
-    This never occurs with the nav code as is - ancient trek versions
-    included shield power in movement allowance if shield control
-    was undamaged */
-    println!("Shield Control supplies energy to complete maneuver.");
+    /* The engines run the main supply dry rather than stop halfway */
+    if let Err(e) = game.ship.energy.take(energy_needed) {
+        io.println(&format!(
+            "Engineering reports:\n  Main energy exhausted by the maneuver, {e}."
+        ));
 
-    game.ship.shield += game.ship.energy as u32;
-    game.ship.energy = 0;
-
-    if game.ship.shield <= 0 {
-        game.ship.shield = 0;
+        game.energy_used += game.ship.energy.drain(energy_needed);
+        return;
     }
 
-    */
+    game.energy_used += energy_needed;
 }
 
-pub fn short_range_scan(game: &mut Game) {
-    let mut s_c;
-
-    let ship_sector_position = game.ship.get_sector_position();
-    let ship_quadrant_position = game.ship.get_quadrant_position();
-    println!("SHIP {:?}", &game.ship.position);
-    println!("QUAD {:?}", &ship_quadrant_position);
-    println!("SECT {:?}", &ship_sector_position);
-
-    s_c = "GREEN";
-
-    if game.ship.energy < STARTING_ENERGY / 10 {
-        s_c = "YELLOW";
-    }
-
-    let current_quadrant = game.get_current_quadrant();
-
-    if current_quadrant.klingons > 0 {
-        s_c = "*RED*";
-    }
-
-    drop(current_quadrant);
-
-    for i in (ship_sector_position.y - 1)..=(ship_sector_position.y + 1) {
-        for j in (ship_sector_position.x - 1)..=(ship_sector_position.x + 1) {
-            if i >= 1 && i <= 8 && j >= 1 && j <= 8 {
-                if game.sector_map[i as usize][j as usize].sector_type == SectorType::BASE {
-                    game.ship.docked = true;
-                    s_c = "DOCKED";
-                    game.ship.energy = STARTING_ENERGY;
-                    game.ship.torps = DEFAULT_PHOTON_TORPEDO_CAPACITY;
-                    print!("Shields dropped for docking purposes.");
-                    game.ship.shield = 0;
-                }
+pub fn short_range_scan(game: &mut Game, io: &mut dyn Frontend) {
+    let ship_sector_position = game.ship.get_sector_position(game.size);
+    let ship_quadrant_position = game.ship.get_quadrant_position(game.size);
+
+    /* Docked when a starbase is in one of the surrounding sectors */
+    game.ship.docked = false;
+
+    for x in (ship_sector_position.x - 1)..=(ship_sector_position.x + 1) {
+        for y in (ship_sector_position.y - 1)..=(ship_sector_position.y + 1) {
+            let sector_position = Vec2 { x, y };
+
+            if game.size.contains_sector(&sector_position)
+                && game
+                    .get_sector(
+                        &game
+                            .size
+                            .position(&ship_quadrant_position, &sector_position),
+                    )
+                    .sector_type
+                    == SectorType::BASE
+            {
+                game.ship.docked = true;
             }
         }
     }
 
+    if game.ship.docked {
+        game.ship.energy = Energy::new(STARTING_ENERGY);
+        game.ship.torps = DEFAULT_PHOTON_TORPEDO_CAPACITY;
+        game.ship.shield = Energy::default();
+        io.event(Event::Docked);
+    }
+
     if game
         .ship
         .get_device(DeviceType::ShortRangeSensors)
         .is_damaged()
     {
-        print!("\n*** Short Range Sensors are out ***");
+        io.print("\n*** Short Range Sensors are out ***");
+        return;
+    }
+
+    game.get_mut_current_quadrant().scan();
+
+    /* The frontend keeps the sensors on screen, nothing to print */
+    if io.has_status_display() {
         return;
     }
 
-    println!("{SRS_1}");
+    let s_c = game.condition();
 
-    for i in 0..8 {
-        for j in 0..8 {
-            let x = (ship_quadrant_position.x * 8) + i;
-            let y = (ship_quadrant_position.y * 8) + j;
-            let v = game.sector_map[x as usize][y as usize].sector_type;
-            print!("{}", TILESTR[v as usize]);
+    let status = [
+        format!("Stardate            {:.2}", game.stardate),
+        format!("Condition           {s_c}"),
+        format!(
+            "Quadrant            {}, {}",
+            ship_quadrant_position.x + 1,
+            ship_quadrant_position.y + 1
+        ),
+        format!(
+            "Sector              {}, {}",
+            ship_sector_position.x + 1,
+            ship_sector_position.y + 1
+        ),
+        format!("Photon Torpedoes    {}", game.ship.torps),
+        format!("Total Energy        {}", game.ship.get_total_energy()),
+        format!("Shields             {}", game.ship.shield),
+        format!("Klingons Remaining  {}", game.klingons_left),
+    ];
+
+    let rows = game.size.quadrant_height as usize;
+    let srs_1 = SRS_1.repeat(game.size.quadrant_width as usize);
+
+    io.println(&srs_1);
+
+    for i in 0..rows.max(status.len()) {
+        if i < rows {
+            for j in 0..game.size.quadrant_width as i32 {
+                let p = game
+                    .size
+                    .position(&ship_quadrant_position, &Vec2 { x: i as i32, y: j });
+                io.print(game.get_sector(&p).sector_type.glyph());
+            }
+        } else {
+            io.print(&" ".repeat(srs_1.len()));
         }
 
-        match i {
-            // Match a single value
-            0 => println!("    Stardate            {:.2}", game.stardate),
-            // Match several values
-            1 => println!("    Condition           {s_c}"),
-            2 => println!(
-                "    Quadrant            {}, {}",
-                ship_quadrant_position.x + 1,
-                ship_quadrant_position.y + 1
-            ),
-            3 => println!(
-                "    Sector              {}, {}",
-                ship_sector_position.x + 1,
-                ship_sector_position.y + 1
-            ),
-            4 => println!("    Photon Torpedoes    {}", game.ship.torps),
-            5 => println!("    Total Energy        {}", game.ship.get_total_energy()),
-            6 => println!("    Shields             {}", game.ship.shield),
-            7 => println!("    Klingons Remaining  {}", game.klingons_left),
-            _ => println!("Ain't special"),
+        match status.get(i) {
+            Some(line) => io.println(&format!("    {line}")),
+            None => io.println(""),
         }
     }
-    println!("{SRS_1}");
-}
 
-pub fn put1bcd(v: u32) {
-    print!("{}", v & 0x0F);
+    io.println(&srs_1);
 }
 
-pub fn putbcd(q: &Quadrant) {
-    put1bcd(q.klingons);
-    put1bcd(q.starbases);
-    put1bcd(q.stars);
+pub fn putbcd(io: &mut dyn Frontend, q: &Quadrant) {
+    io.print(&q.scan_code());
 }
 
-pub fn long_range_scan(game: &mut Game) {
+pub fn long_range_scan(game: &mut Game, io: &mut dyn Frontend) {
     if game
         .ship
         .get_device(DeviceType::LongRangeSensors)
-        .is_inoperable()
+        .is_inoperable(io)
     {
         return;
     }
 
-    let ship_quadrant_position = game.ship.get_quadrant_position();
+    let ship_quadrant_position = game.ship.get_quadrant_position(game.size);
 
-    println!(
+    io.println(&format!(
         "Long Range Scan for Quadrant {}, {}\n",
-        ship_quadrant_position.y, ship_quadrant_position.x
-    );
+        ship_quadrant_position.x + 1,
+        ship_quadrant_position.y + 1
+    ));
 
     for i in (ship_quadrant_position.x - 1)..=(ship_quadrant_position.x + 1) {
-        print!("{}\n:", LRS_1);
+        io.print(&format!("{}\n:", LRS_1));
         for j in (ship_quadrant_position.y - 1)..=(ship_quadrant_position.y + 1) {
-            print!(" ");
-            if i >= 0 && i < 8 && j >= 0 && j < 8 {
-                game.quadrant_map[i as usize][j as usize].visited = true;
-                putbcd(&game.quadrant_map[i as usize][j as usize]);
+            io.print(" ");
+            if game.size.contains_quadrant(&Vec2 { x: i, y: j }) {
+                game.quadrant_map[i as usize][j as usize].scan();
+                putbcd(io, &game.quadrant_map[i as usize][j as usize]);
             } else {
-                print!("***");
+                io.print("***");
             }
-            print!(" :");
+            io.print(" :");
         }
-        println!("");
+        io.println("");
     }
 
-    println!("{}", LRS_1);
+    io.println(LRS_1);
 }
 
-pub fn phaser_control(game: &mut Game) {
+pub fn phaser_control(game: &mut Game, io: &mut dyn Frontend) {
     if game
         .ship
         .get_device(DeviceType::PhaserControl)
-        .is_inoperable()
+        .is_inoperable(io)
     {
         return;
     }
 
-    if game.no_klingons_in_current_quadrant() {
+    if game.no_klingons_in_current_quadrant(io) {
         return;
     }
 
@@ -1356,27 +1578,39 @@ pub fn phaser_control(game: &mut Game) {
         .get_device(DeviceType::LibraryComputer)
         .is_damaged()
     {
-        println!("Computer failure hampers accuracy.");
+        io.println("Computer failure hampers accuracy.");
     }
 
-    println!(
+    io.println(&format!(
         "Phasers locked on target;\n
     Energy available = {} units",
         game.ship.energy
-    );
+    ));
+
+    let energy = game.ship.energy.units();
+
+    let Some(phaser_energy) = ask_i32(io, "Number of units to fire", 0, 10000, |units| {
+        if units as u32 > energy {
+            Err(format!("Not enough energy available, only {energy} units."))
+        } else {
+            Ok(())
+        }
+    }) else {
+        return;
+    };
 
-    let mut phaser_energy = input_i32("Number of units to fire", 0, 10000) as u32;
+    let mut phaser_energy = phaser_energy as u32;
 
     if phaser_energy == 0 {
         return;
     }
 
-    if game.ship.energy < phaser_energy {
-        println!("Not enough energy available.");
+    if let Err(e) = game.ship.energy.take(phaser_energy) {
+        io.println(&format!("Phasers can't fire, {e}."));
         return;
     }
 
-    game.ship.energy -= phaser_energy;
+    game.energy_used += phaser_energy;
 
     /* We can fire up to nearly 3000 points of energy so we do this
     bit in 32bit math */
@@ -1386,17 +1620,17 @@ pub fn phaser_control(game: &mut Game) {
         .get_device(DeviceType::LibraryComputer)
         .is_damaged()
     {
-        phaser_energy *= get_rand(100) as u32;
+        phaser_energy *= get_rand(&mut game.rng, 100) as u32;
     } else {
         phaser_energy *= 100;
     }
 
-    let ship_quadrant_position = game.ship.get_quadrant_position();
+    let ship_quadrant_position = game.ship.get_quadrant_position(game.size);
     let alive_local_klingons = game
         .klingons
         .iter_mut()
         .filter(|k| !k.destroyed)
-        .filter(|k| k.get_quadrant_position() == ship_quadrant_position)
+        .filter(|k| k.get_quadrant_position(game.size) == ship_quadrant_position)
         .collect::<Vec<_>>();
 
     let h1 = phaser_energy / alive_local_klingons.len() as u32;
@@ -1405,47 +1639,38 @@ pub fn phaser_control(game: &mut Game) {
 
     for k in alive_local_klingons {
         /* We are now 32bit with four digits accuracy */
-        let mut h = h1 * (get_rand(100) as u32 + 200);
+        let mut h = h1 * (get_rand(&mut game.rng, 100) as u32 + 200);
         /* Takes us down to 2 digit accuracy */
 
-        let hp: Box<dyn HasPosition> = Box::new(*k);
-        let d = game.ship.distance_to(&hp);
+        let d = game.ship.distance_to(k);
         h = (h as f32 / d) as u32;
 
-        if h <= 15 * k.energy {
+        let sector = k.get_sector_position(game.size);
+
+        if h <= 15 * k.energy.units() {
             /* was 0.15 */
-            println!(
-                "Sensors show no damage to enemy at
-{}, {}\n",
-                k.get_position().x,
-                k.get_position().y
-            );
+            io.event(Event::KlingonUnharmed { sector });
         } else {
-            println!(
-                "{} unit hit on Klingon at sector
-{}, {}",
-                h,
-                k.get_position().x,
-                k.get_position().y
-            );
-
-            if k.energy < h {
-                println!("*** Klingon Destroyed ***");
-                k.energy = 0;
+            io.event(Event::KlingonHit {
+                sector,
+                damage: h,
+                remaining: k.energy.units().saturating_sub(h),
+            });
+
+            if k.energy.take(h).is_err() {
+                io.event(Event::KlingonDestroyed { sector });
+                k.energy = Energy::default();
                 k.destroyed = true;
 
                 game.sector_map[k.get_position().x as usize][k.get_position().y as usize]
                     .sector_type = SectorType::SPACE;
 
+                /* Counted off the totals once, after the volley */
                 destroyed_klingon_ships += 1;
 
-                game.klingons_left -= 1;
-                if game.klingons_left == 0 {
+                if destroyed_klingon_ships == game.klingons_left {
                     break;
                 }
-            } else {
-                k.energy -= h;
-                println!("   (Sensors show {} units remaining.)\n", k.energy);
             }
         }
     }
@@ -1454,105 +1679,96 @@ pub fn phaser_control(game: &mut Game) {
         /* Minus a Klingon.. */
         let current_quadrant = game.get_mut_current_quadrant();
         current_quadrant.klingons -= destroyed_klingon_ships;
-        drop(current_quadrant);
 
         game.klingons_left -= destroyed_klingon_ships;
 
         if game.klingons_left == 0 {
-            won_game(game);
+            won_game(game, io);
         }
     }
 
-    klingons_shoot(game);
+    klingons_shoot(game, io);
 }
 
-pub fn photon_torpedoes(game: &mut Game) {
+pub fn photon_torpedoes(game: &mut Game, io: &mut dyn Frontend) {
     if game.ship.torps == 0 {
-        print!("All photon torpedoes expended");
+        io.print("All photon torpedoes expended");
         return;
     }
 
     if game
         .ship
         .get_device(DeviceType::PhotonTubes)
-        .is_inoperable()
+        .is_inoperable(io)
     {
         return;
     }
 
-    let c1 = get_course("Ensign Chekov");
+    let c1 = get_course(io, "Ensign Chekov");
+
+    if c1.is_none() {
+        return;
+    }
 
-    if c1 == None {
+    if let Err(e) = game.ship.energy.take(TORPEDO_ENERGY) {
+        io.println(&format!("Not enough energy to launch, {e}."));
         return;
     }
 
-    /* FIXME: energy underflow check ? */
-    game.ship.energy -= 2;
+    game.energy_used += TORPEDO_ENERGY;
     game.ship.torps -= 1;
 
     let c1 = c1.unwrap();
 
-    let c1_position = get_new_position(c1);
-
-    // depends on c1
-    println!(
-        "c1 {c1}, c1_position_: {}, {}",
-        c1_position.x, c1_position.y
-    );
-
-    print!("Torpedo Track:");
-
-    let ship_quadrant_position = game.ship.get_quadrant_position();
+    io.print("Torpedo Track:");
 
-    let mut torpedo_position = game.ship.position.clone();
+    let ship_quadrant_position = game.ship.get_quadrant_position(game.size);
 
-    loop {
-        torpedo_position.x += c1_position.x;
-        torpedo_position.y += c1_position.y;
-
-        if torpedo_position.get_quadrant_position() != ship_quadrant_position {
+    for torpedo_position in Track::new(&game.ship.position, c1) {
+        if torpedo_position.is_outside(game.size)
+            || torpedo_position.get_quadrant_position(game.size) != ship_quadrant_position
+        {
             break;
         }
 
-        let torpedo_sector_position = torpedo_position.get_sector_position();
-
-        println!(
-            "    {}, {}",
-            torpedo_sector_position.x, torpedo_sector_position.y
-        );
+        io.event(Event::TorpedoTrack {
+            sector: torpedo_position.get_sector_position(game.size),
+        });
 
         let sector_type = game.get_sector(&torpedo_position).sector_type;
         /* In certain corner cases the first trace we'll step is
         ourself. If so treat it as space */
         if sector_type != SectorType::SPACE && sector_type != SectorType::SHIP {
-            torpedo_hit(game, &torpedo_position);
-            klingons_shoot(game);
+            torpedo_hit(game, io, &torpedo_position);
+            klingons_shoot(game, io);
             return;
         }
     }
 
-    println!("Torpedo Missed");
+    io.event(Event::TorpedoMissed);
 
-    klingons_shoot(game);
+    klingons_shoot(game, io);
 }
 
-pub fn torpedo_hit(game: &mut Game, torpedo_position: &Vec2) {
-    let sector_type = game.get_sector(&torpedo_position).sector_type;
-    let torpedo_sector_position = torpedo_position.get_sector_position();
+pub fn torpedo_hit(game: &mut Game, io: &mut dyn Frontend, torpedo_position: &Vec2) {
+    let sector_type = game.get_sector(torpedo_position).sector_type;
+    let torpedo_sector_position = torpedo_position.get_sector_position(game.size);
 
     match sector_type {
-        SectorType::STAR => println!(
-            "Star at {}, {} absorbed torpedo energy.\n",
-            torpedo_sector_position.x, torpedo_sector_position.y
-        ),
-        SectorType::KLINGON => {
-            println!("*** Klingon Destroyed ***");
-
-            game.get_mut_current_quadrant().starbases -= 1;
+        SectorType::STAR => io.event(Event::TorpedoAbsorbed {
+            sector: torpedo_sector_position,
+        }),
+        t if t.is_klingon() && klingon_evades(game, io, torpedo_position) => return,
+        t if t.is_klingon() => {
+            io.event(Event::KlingonDestroyed {
+                sector: torpedo_sector_position,
+            });
+
+            game.get_mut_current_quadrant().klingons -= 1;
             game.klingons_left -= 1;
 
             if game.klingons_left == 0 {
-                won_game(game);
+                won_game(game, io);
             }
 
             game.klingons
@@ -1562,7 +1778,7 @@ pub fn torpedo_hit(game: &mut Game, torpedo_position: &Vec2) {
                 .collect::<Vec<_>>()
                 .first_mut()
                 .unwrap()
-                .energy = 0;
+                .energy = Energy::default();
             game.klingons
                 .iter_mut()
                 .filter(|k| !k.destroyed)
@@ -1573,26 +1789,21 @@ pub fn torpedo_hit(game: &mut Game, torpedo_position: &Vec2) {
                 .destroyed = true;
         }
         SectorType::BASE => {
-            println!("*** Starbase Destroyed ***");
+            io.event(Event::StarbaseDestroyed {
+                sector: torpedo_sector_position,
+            });
             game.starbases_left -= 1;
 
             if game.starbases_left == 0
             /*&&
             game.klingons_left <= game.time_up */
             {
-                /* showfile ? FIXME */
-                println!(
-                    "That does it, Captain!!
-You are hereby relieved of command
-and sentenced to 99 stardates of hard
-labor on Cygnus 12!!"
-                );
-                resign_commision(game);
+                lose_game(game, io, LossReason::RelievedOfCommand);
             }
 
-            println!(
+            io.println(
                 "Starfleet Command reviewing your record to consider
-    court martial!"
+    court martial!",
             );
 
             game.ship.docked = false; /* Undock */
@@ -1607,17 +1818,62 @@ labor on Cygnus 12!!"
                 .unwrap()
                 .destroyed = true;
         }
-        _ => println!("!!!!"),
+        _ => io.println("!!!!"),
     }
 
     game.get_mut_sector(torpedo_position).sector_type = SectorType::SPACE;
 }
 
-pub fn damage_control(game: &mut Game) {
+/* Commanders may dodge into a free sector next to them, returns true if
+the torpedo missed that way */
+fn klingon_evades(game: &mut Game, io: &mut dyn Frontend, torpedo_position: &Vec2) -> bool {
+    let size = game.size;
+
+    let Some(idx) = game
+        .klingons
+        .iter()
+        .position(|k| !k.destroyed && k.get_position() == torpedo_position)
+    else {
+        return false;
+    };
+
+    let class = game.klingons[idx].class;
+
+    if get_rand(&mut game.rng, 100) as u32 > class.evasion() {
+        return false;
+    }
+
+    let quadrant = torpedo_position.get_quadrant_position(size);
+    let free: Vec<Vec2> = (torpedo_position.x - 1..=torpedo_position.x + 1)
+        .flat_map(|x| (torpedo_position.y - 1..=torpedo_position.y + 1).map(move |y| Vec2 { x, y }))
+        .filter(|p| !p.is_outside(size) && p.get_quadrant_position(size) == quadrant)
+        .filter(|p| game.get_sector(p).sector_type == SectorType::SPACE)
+        .collect();
+
+    if free.is_empty() {
+        return false;
+    }
+
+    let to = free[get_rand(&mut game.rng, free.len() as i32) as usize - 1];
+
+    game.get_mut_sector(torpedo_position).sector_type = SectorType::SPACE;
+    game.get_mut_sector(&to).sector_type = class.sector_type();
+    game.klingons[idx].move_to(&to);
+
+    io.event(Event::KlingonEvaded {
+        class,
+        sector: torpedo_position.get_sector_position(size),
+        to: to.get_sector_position(size),
+    });
+
+    true
+}
+
+pub fn damage_control(game: &mut Game, io: &mut dyn Frontend) {
     let mut repair_cost;
 
     if game.ship.get_device(DeviceType::DamageControl).is_damaged() {
-        print!("Damage Control report not available.");
+        io.print("Damage Control report not available.");
     }
 
     /* Offer repair if docked */
@@ -1629,24 +1885,24 @@ pub fn damage_control(game: &mut Game) {
             let device = game.ship.get_device(DeviceType::from_u8(i));
 
             if device.is_damaged() {
-                repair_cost = repair_cost + 10;
+                repair_cost += 10;
             }
         }
 
         if repair_cost > 0 {
-            repair_cost = repair_cost + game.d4;
+            repair_cost += game.d4;
 
             if repair_cost >= 100 {
                 repair_cost = 90; /* 0.9 */
             }
 
-            println!(
+            io.println(&format!(
                 "Technicians standing by to effect repairs to your
 ship;\nEstimated time to repair: {} stardates.",
                 repair_cost
-            );
+            ));
 
-            if yesno("Will you authorize the repair order?", false) {
+            if io.yesno("Will you authorize the repair order?", false) {
                 for i in 0..DeviceType::DeviceNum as u8 {
                     let device = game.ship.get_mut_device(DeviceType::from_u8(i));
 
@@ -1669,139 +1925,181 @@ ship;\nEstimated time to repair: {} stardates.",
         return;
     }
 
-    print!("Device            State of Repair");
+    io.print("Device            State of Repair");
 
-    for i in 1..8 as u8 {
+    for i in 1..8 {
         let device = game.ship.get_device(DeviceType::from_u8(i));
-        println!("{:25}{:6}\n", device.name, device.damage);
+        io.println(&format!("{:25}{:6}\n", device.name, device.damage));
 
-        println!("");
+        io.println("");
     }
 }
 
-pub fn shield_control(game: &mut Game) {
+pub fn shield_control(game: &mut Game, io: &mut dyn Frontend) {
     if game
         .ship
         .get_device(DeviceType::ShieldControl)
-        .is_inoperable()
+        .is_inoperable(io)
     {
         return;
     }
 
-    println!("Energy available = {}", game.ship.get_total_energy());
-
-    //let i = input_i32() as u32;
+    io.println(&format!(
+        "Energy available = {}",
+        game.ship.get_total_energy()
+    ));
 
-    let i = input_i32("Input number of units to shields", 0, 10000) as u32;
+    let total = game.ship.get_total_energy();
 
-    if game.ship.shield == i {
-        if i >= game.ship.get_total_energy() {
-            println!(
+    let Some(i) = ask_i32(io, "Input number of units to shields", 0, 10000, |units| {
+        if units as u32 > total {
+            Err(format!(
                 "Shield Control Reports:\n
-      'This is not the Federation Treasury.'"
-            );
+      'This is not the Federation Treasury, only {total} units available.'"
+            ))
+        } else {
+            Ok(())
         }
-        println!("<Shields Unchanged>");
+    }) else {
+        return;
+    };
+
+    let i = i as u32;
+
+    if game.ship.shield.units() == i {
+        io.println("<Shields Unchanged>");
         return;
     }
 
-    game.ship.energy = game.ship.energy + game.ship.shield - i;
-    game.ship.shield = i;
+    if let Err(e) = game.ship.set_shields(i) {
+        io.println(&format!(
+            "Shield Control Reports:\n\n      'Transfer failed, {e}.'"
+        ));
+        return;
+    }
 
-    println!(
+    io.println(&format!(
         "Deflector Control Room report:\n
   'Shields now at {} units per your command.'\n",
         game.ship.shield
-    );
+    ));
 }
 
-pub fn library_computer(game: &mut Game) {
+/* What the library computer can do, by number */
+pub const COMPUTER_FUNCTIONS: [&str; 6] = [
+    "Cumulative Galactic Record",
+    "Status Report",
+    "Photon Torpedo Data",
+    "Starbase Nav Data",
+    "Direction/Distance Calculator",
+    "Galaxy 'Region Name' Map",
+];
+
+pub fn library_computer(game: &mut Game, io: &mut dyn Frontend) {
     if game
         .ship
         .get_device(DeviceType::LibraryComputer)
-        .is_inoperable()
+        .is_inoperable(io)
     {
         return;
     }
 
-    let i = input_i32("Computer active and awaiting command", 0, 9);
+    let Some(i) = io.input_i32("Computer active and awaiting command", 0, 9) else {
+        return;
+    };
 
     //println!("selection: {i}");
 
     match i {
-        0 => galactic_record(&game),
-        1 => status_report(&game),
+        0 => galactic_record(game, io),
+        1 => status_report(game, io),
 
-        2 => torpedo_data(&game),
+        2 => torpedo_data(game, io),
 
-        3 => nav_data(&game),
-        4 => dirdist_calc(&game),
-        5 => galaxy_map(&game),
+        3 => nav_data(game, io),
+        4 => dirdist_calc(game, io),
+        5 => galaxy_map(game, io),
 
         _ =>
         /* FIXME: showfile */
         {
-            println!(
-                "Functions available from Library-Computer:\n\n
-  0 = Cumulative Galactic Record\n
-  1 = Status Report\n
-  2 = Photon Torpedo Data\n
-  3 = Starbase Nav Data\n
-  4 = Direction/Distance Calculator\n
-  5 = Galaxy 'Region Name' Map"
-            )
+            let functions: Vec<String> = COMPUTER_FUNCTIONS
+                .iter()
+                .enumerate()
+                .map(|(i, name)| format!("  {i} = {name}\n"))
+                .collect();
+
+            io.println(&format!(
+                "Functions available from Library-Computer:\n\n\n{}",
+                functions.join("\n").trim_end()
+            ))
         }
     }
 }
 
-pub fn galactic_record(game: &Game) {
-    let game_ship_quadrant = game.ship.get_quadrant_position();
-    println!(
+pub fn galactic_record(game: &Game, io: &mut dyn Frontend) {
+    let game_ship_quadrant = game.ship.get_quadrant_position(game.size);
+    io.println(&format!(
         "\n     Computer Record of Galaxy for Quadrant {},{}\n",
-        game_ship_quadrant.x, game_ship_quadrant.y
-    );
-    println!("     1     2     3     4     5     6     7     8");
+        game_ship_quadrant.x + 1,
+        game_ship_quadrant.y + 1
+    ));
+    io.println(&grid_header(3, game.size.width));
 
-    for i in 0..8 {
-        print!("{}\n{}", GR_1, i);
+    for (i, row) in game.quadrant_map.iter().enumerate() {
+        io.println(&grid_rule(3, game.size.width));
 
-        for j in 0..8 {
-            print!("   ");
+        let cells: Vec<String> = row
+            .iter()
+            .map(|q| q.record.clone().unwrap_or_else(|| "***".to_string()))
+            .collect();
 
-            if game.quadrant_map[i][j].visited {
-                putbcd(&game.quadrant_map[i][j]);
-            } else {
-                print!("***");
-            }
-        }
-        println!("");
+        io.println(&format!("{:>2}  {}", i + 1, cells.join("   ")));
     }
 
-    println!("{}", GR_1);
+    io.println(&grid_rule(3, game.size.width));
+}
+
+/* Column numbers and rules for maps of the galaxy, one column of five
+characters per quadrant after the indent */
+fn grid_header(indent: usize, quadrants: u32) -> String {
+    let numbers: Vec<String> = (1..=quadrants).map(|n| format!("{n:^5}")).collect();
+
+    format!("{}{}", " ".repeat(indent), numbers.join(" "))
+        .trim_end()
+        .to_string()
+}
+
+fn grid_rule(indent: usize, quadrants: u32) -> String {
+    format!(
+        "{}{}",
+        " ".repeat(indent),
+        vec!["-----"; quadrants as usize].join(" ")
+    )
 }
 
-pub fn status_report(game: &Game) {
+pub fn status_report(game: &Game, io: &mut dyn Frontend) {
     let mut plural = "";
 
-    print!("   Status Report:\n");
+    io.println("   Status Report:");
 
     if game.klingons_left > 1 {
         plural = STR_S;
     }
 
     /* Assumes fixed point is single digit fixed */
-    println!(
+    io.println(&format!(
         "Klingon{} Left: {}\n
 Mission must be completed in {} stardates",
         plural,
         game.klingons_left,
         game.time_up - game.stardate
-    );
+    ));
 
     if game.starbases_left < 1 {
-        println!(
+        io.println(
             "Your stupidity has left you on your own in the galaxy\n
- -- you have no starbases left!"
+ -- you have no starbases left!",
         );
     } else {
         plural = "";
@@ -1810,17 +2108,17 @@ Mission must be completed in {} stardates",
             plural = STR_S;
         }
 
-        println!(
+        io.println(&format!(
             "The Federation is maintaining {} starbase{} in the galaxy\n",
             game.starbases_left, plural
-        );
+        ));
     }
 }
 
-pub fn torpedo_data(game: &Game) {
+pub fn torpedo_data(game: &Game, io: &mut dyn Frontend) {
     let mut plural = "";
 
-    let ship_quadrant_position = game.ship.get_quadrant_position();
+    let ship_quadrant_position = game.ship.get_quadrant_position(game.size);
 
     let klingons_in_quadrant = game.quadrant_map[ship_quadrant_position.x as usize]
         [ship_quadrant_position.y as usize]
@@ -1829,10 +2127,10 @@ pub fn torpedo_data(game: &Game) {
     let mut klingons: Vec<&Klingon> = Vec::new();
 
     for k in &game.klingons {
-        let klingon_quadrant_position = k.get_quadrant_position();
+        let klingon_quadrant_position = k.get_quadrant_position(game.size);
 
         if klingon_quadrant_position == ship_quadrant_position {
-            klingons.push(&k);
+            klingons.push(k);
         }
     }
 
@@ -1845,140 +2143,185 @@ pub fn torpedo_data(game: &Game) {
         plural = STR_S;
     }
 
-    println!("From Enterprise to Klingon battlecruiser{}:\n\n", plural);
+    io.println(&format!(
+        "From Enterprise to Klingon battlecruiser{}:\n\n",
+        plural
+    ));
 
     for k in klingons {
-        if k.energy > 0 {
-            compute_vector(&k.position, &game.ship.position);
+        if !k.destroyed {
+            compute_vector(io, game.size, &game.ship.position, &k.position);
         }
     }
 }
 
-pub fn nav_data(game: &Game) {
-    let ship_quadrant_position = game.ship.get_quadrant_position();
+pub fn nav_data(game: &Game, io: &mut dyn Frontend) {
+    let ship_quadrant_position = game.ship.get_quadrant_position(game.size);
 
     if game.quadrant_map[ship_quadrant_position.x as usize][ship_quadrant_position.y as usize]
         .starbases
-        <= 0
+        == 0
     {
-        println!(
+        io.println(
             "Mr. Spock reports,\n
-          'Sensors show no starbases in this quadrant.'"
+          'Sensors show no starbases in this quadrant.'",
         );
         return;
     }
 
     for starbase in &game.starbases {
-        let starbase_quadrant_position = starbase.get_quadrant_position();
+        let starbase_quadrant_position = starbase.get_quadrant_position(game.size);
 
         if ship_quadrant_position == starbase_quadrant_position {
-            compute_vector(&starbase.position, &game.ship.position);
+            compute_vector(io, game.size, &game.ship.position, &starbase.position);
         }
     }
 }
 
-/* Q: do we want to support fractional co-ords ? */
-
-pub fn dirdist_calc(game: &Game) {
-    /*
-    int16_t c1, a, w1, x;
-    print!("Direction/Distance Calculator\n"
-           "You are at quadrant %d,%d sector %d,%d\n\n"
-           "Please enter initial X coordinate: ",
-           quad_y, quad_x,
-           FROM_FIXED00(ship_y), FROM_FIXED00(ship_x));
-
-    c1 = TO_FIXED00(input_int());
-    if (c1 < 0 || c1 > 900 )
+pub fn dirdist_calc(game: &Game, io: &mut dyn Frontend) {
+    let ship_quadrant_position = game.ship.get_quadrant_position(game.size);
+    let ship_sector_position = game.ship.get_sector_position(game.size);
+
+    io.println(&format!(
+        "Direction/Distance Calculator
+You are at quadrant {}, {} sector {}, {}\n",
+        ship_quadrant_position.x + 1,
+        ship_quadrant_position.y + 1,
+        ship_sector_position.x + 1,
+        ship_sector_position.y + 1
+    ));
+
+    let size = game.size;
+    let Some(qx) = io.input_i32(
+        "Please enter final quadrant X coordinate:",
+        1,
+        size.height as i32,
+    ) else {
         return;
-
-    fprint!("Please enter initial Y coordinate: ", stdout);
-    a = TO_FIXED00(input_int());
-    if (a < 0 || a > 900)
+    };
+    let Some(qy) = io.input_i32(
+        "Please enter final quadrant Y coordinate:",
+        1,
+        size.width as i32,
+    ) else {
         return;
-
-    fprint!("Please enter final X coordinate: ", stdout);
-    w1 = TO_FIXED00(input_int());
-    if (w1 < 0 || w1 > 900)
+    };
+    let Some(sx) = io.input_i32(
+        "Please enter final sector X coordinate:",
+        1,
+        size.quadrant_height as i32,
+    ) else {
         return;
-
-    fprint!("Please enter final Y coordinate: ", stdout);
-    x = TO_FIXED00(input_int());
-    if (x < 0 || x > 900)
+    };
+    let Some(sy) = io.input_i32(
+        "Please enter final sector Y coordinate:",
+        1,
+        size.quadrant_width as i32,
+    ) else {
         return;
-    compute_vector(w1, x, c1, a);
-    */
-}
-
-pub fn galaxy_map(game: &Game) {
-    return;
-    /*
-    println!("\n                   The Galaxy\n");
-    println!("    1     2     3     4     5     6     7     8");
+    };
 
-    for i in 0..8 {
-        print!("%s%d ", gm_1, i);
+    let destination = size.position(
+        &Vec2 {
+            x: qx - 1,
+            y: qy - 1,
+        },
+        &Vec2 {
+            x: sx - 1,
+            y: sy - 1,
+        },
+    );
 
-        quadrant_name(1, i, 1);
+    compute_vector(io, game.size, &game.ship.position, &destination);
+}
 
-        let j0 = (int) (11 - (strlen(quadname) / 2));
+pub fn galaxy_map(game: &Game, io: &mut dyn Frontend) {
+    let ship_quadrant_position = game.ship.get_quadrant_position(game.size);
 
-        for j in 0..j0 {
-            print!(" ");
-        }
+    /* Two regions to a row, splitting it in half */
+    let half = (game.size.width as i32 / 2).max(1);
+    let west_width = 6 * half as usize;
+    let east_width = 6 * (game.size.width as usize).saturating_sub(half as usize);
 
-        fprint!(quadname, stdout);
+    /* The Enterprise's quadrant gets a double line above and below */
+    let separator = |row: i32| -> String {
+        let mut line = grid_rule(2, game.size.width);
 
-        for j in 0..j0 {
-            print!(" ");
+        if row == ship_quadrant_position.x || row == ship_quadrant_position.x + 1 {
+            let start = 2 + 6 * ship_quadrant_position.y as usize;
+            line.replace_range(start..start + 5, "=====");
         }
 
-        if (!(strlen(quadname) % 2))
-            putchar(' ');
+        line
+    };
 
-        quadrant_name(1, i, 5);
+    io.println(&format!(
+        "\n{}\n",
+        format!("{:^w$}", "The Galaxy", w = 2 + 6 * game.size.width as usize).trim_end()
+    ));
+    io.println(&grid_header(2, game.size.width));
 
-        j0 = (int) (12 - (strlen(quadname) / 2));
+    for i in 0..game.size.height as i32 {
+        io.println(&separator(i));
 
-        for j in 0..j0 {
-            print!(" ");
-        }
+        let west = get_quadrant_name(game.size, &Vec2 { x: i, y: 0 }, false);
+        let east = get_quadrant_name(game.size, &Vec2 { x: i, y: half }, false);
 
-        print!(quadname);
+        io.println(format!("{:<2}{:^west_width$}{:^east_width$}", i + 1, west, east).trim_end());
     }
 
-    print!("{GM_1}");
-     */
-}
+    io.println(&separator(game.size.height as i32));
 
-pub fn compute_vector(p1: &Vec2, p2: &Vec2) {
-    print!("  DIRECTION = ");
+    io.println(&format!(
+        "\nThe Enterprise is in quadrant {}, {}, {}",
+        ship_quadrant_position.x + 1,
+        ship_quadrant_position.y + 1,
+        get_quadrant_name(game.size, &ship_quadrant_position, true)
+    ));
 }
 
-pub fn show_file(filename: &str) {
-    let contents = read_to_string(filename).expect("Should have been able to read the file");
+pub fn compute_vector(io: &mut dyn Frontend, size: GalaxySize, from: &Vec2, to: &Vec2) {
+    match from.course_to(to, size) {
+        Some((course, warp)) => io.println(&format!(
+            "  DIRECTION = {:.2}\n  DISTANCE = {:.2} (warp {:.2})",
+            course,
+            warp * size.quadrant_width as f32,
+            warp
+        )),
+        None => io.println("  You are already there, Captain."),
+    }
+}
 
-    println!("{contents}");
+pub fn show_file(io: &mut dyn Frontend, filename: &str) {
+    match load_asset(filename) {
+        Some(contents) => io.println(&contents),
+        None => io.println(&format!("Unknown text {filename}")),
+    }
 }
 
-pub fn intro() {
-    show_file("assets/startrek.intro");
+pub fn intro(io: &mut dyn Frontend) {
+    show_file(io, "startrek.intro");
 
-    if yesno("Do you need instructions?", false) {
-        show_file("assets/startrek.doc");
+    if io.yesno("Do you need instructions?", false) {
+        show_file(io, "startrek.doc");
     }
 
-    show_file("assets/startrek.logo");
+    show_file(io, "startrek.logo");
 }
 
-pub fn show_orders(game: &Game) {
+pub fn show_orders(game: &Game, io: &mut dyn Frontend) {
     let (plural, plural_2) = if game.starbases_left != 1 {
         ("are", "s")
     } else {
         ("is", "")
     };
 
-    println!(
+    io.println(&format!(
+        "Galaxy seed {}, difficulty {}\n",
+        game.seed, game.difficulty.level
+    ));
+
+    io.println(&format!(
         "Your orders are as follows:
  Destroy the {} Klingon warships which have invaded
  the galaxy before they can attack Federation Headquarters
@@ -1986,94 +2329,324 @@ pub fn show_orders(game: &Game) {
  {} starbase{} in the galaxy for resupplying your ship.
  ",
         game.klingons_left, game.stardate, game.time_days, plural, game.starbases_left, plural_2
-    );
+    ));
 
-    any_key("Hit any key to accept command.");
+    io.any_key("Hit any key to accept command.");
 }
 
-pub fn new_quadrant(game: &mut Game) {
+pub fn new_quadrant(game: &mut Game, io: &mut dyn Frontend) {
     /* Random factor for damage repair. We compute it on each new
     quadrant to stop the user just retrying until they get a number
     they like. The conversion here was wrong and now copies the BASIC
     code generate 0.00 to 0.49 */
-    game.d4 = (get_rand(50) - 1) as i32; /* Used for computing damage repair time */
+    game.d4 = get_rand(&mut game.rng, 50) - 1; /* Used for computing damage repair time */
 
     //println!("{:?}", &game.ship);
 
-    let mut tmp_current_quadrant = game.get_mut_current_quadrant();
-    tmp_current_quadrant.visited = true;
-    drop(tmp_current_quadrant);
+    game.get_mut_current_quadrant().scan();
 
     let current_quadrant = game.get_current_quadrant();
     //println!("{:?}", &current_quadrant);
 
-    if !game.ship.is_outside() {
-        let quadname = &current_quadrant.name;
+    if !game.ship.is_outside(game.size) {
+        io.event(Event::QuadrantEntered {
+            quadrant: current_quadrant.position,
+            name: current_quadrant.name.clone(),
+            mission_start: game.time_start == game.stardate,
+        });
+    }
+
+    if current_quadrant.klingons > 0 {
+        io.println("Combat Area  Condition Red");
 
-        if game.time_start != game.stardate {
-            println!("Now entering {quadname} quadrant...\n");
-        } else {
-            println!(
-                "\nYour mission begins with your starship located
-in the galactic quadrant {quadname}\n"
-            );
+        if game.ship.shield.units() < 200 {
+            io.println("Shields Dangerously Low");
         }
     }
+}
 
-    if current_quadrant.klingons > 0 {
-        println!("Combat Area  Condition Red");
+pub fn save_command(game: &Game, io: &mut dyn Frontend) {
+    let filename = get_save_filename(io);
 
-        if game.ship.shield < 200 {
-            println!("Shields Dangerously Low");
+    match save_game(game, &filename) {
+        Ok(()) => io.println(&format!("Mission log saved to {filename}")),
+        Err(e) => io.println(&format!("Unable to save to {filename}: {e}")),
+    }
+}
+
+pub fn load_command(game: &mut Game, io: &mut dyn Frontend) {
+    let filename = get_save_filename(io);
+
+    match load_game(&filename) {
+        Ok(loaded) => {
+            *game = loaded;
+
+            io.println(&format!("Mission log restored from {filename}\n"));
+
+            short_range_scan(game, io);
         }
+        Err(e) => io.println(&format!("Unable to load {filename}: {e}")),
     }
 }
 
-pub fn run_game() -> bool {
-    let mut game = Game::initialize();
+fn get_save_filename(io: &mut dyn Frontend) -> String {
+    let filename = io.input_string(&format!("File name ({DEFAULT_SAVE_FILE})"));
 
-    show_orders(&game);
+    if filename.trim().is_empty() {
+        DEFAULT_SAVE_FILE.to_string()
+    } else {
+        filename.trim().to_string()
+    }
+}
 
-    new_quadrant(&mut game);
+/* Brief the captain and show where the mission begins */
+pub fn begin_mission(game: &mut Game, io: &mut dyn Frontend) {
+    show_orders(game, io);
 
-    short_range_scan(&mut game);
+    new_quadrant(game, io);
+
+    short_range_scan(game, io);
+}
+
+/* Without energy to move the mission is over, returns true if so */
+pub fn check_stranded(game: &mut Game, io: &mut dyn Frontend) -> bool {
+    if game.ship.is_unable_to_navigate() {
+        show_file(io, "startrek.fatal");
+
+        lose_game(game, io, LossReason::Stranded);
+
+        return true;
+    }
+
+    false
+}
+
+pub fn execute_command(game: &mut Game, io: &mut dyn Frontend, cmd: &str) {
+    let command = match parse_command(cmd) {
+        Ok(Some(command)) => command,
+        Ok(None) => {
+            show_file(io, "commands.txt");
+            return;
+        }
+        Err(e) => {
+            io.println(&format!("{e}\n"));
+            show_file(io, "commands.txt");
+            return;
+        }
+    };
 
+    if command.keyword == "hel" {
+        show_help(io, command.args.first().map(String::as_str));
+        return;
+    }
+
+    /* Values typed after the command answer its prompts */
+    let mut inline = InlineAnswers::new(io, command.args);
+
+    {
+        let io: &mut dyn Frontend = &mut inline;
+
+        match command.keyword {
+            "nav" => course_control(game, io),
+            "srs" => short_range_scan(game, io),
+            "lrs" => long_range_scan(game, io),
+
+            "pha" => phaser_control(game, io),
+            "tor" => photon_torpedoes(game, io),
+
+            "shi" => shield_control(game, io),
+
+            "dam" => damage_control(game, io),
+            "com" => library_computer(game, io),
+
+            "sav" => save_command(game, io),
+            "loa" => load_command(game, io),
+
+            "xxx" => resign_commision(game, io),
+
+            _ => show_file(io, "commands.txt"),
+        }
+    }
+
+    let leftover = inline.leftover();
+
+    if !leftover.is_empty() {
+        inline.println(&format!("(Ignored {})", leftover.join(" ")));
+    }
+
+    /* The rest of the galaxy doesn't wait */
+    if !game.game_over {
+        fleet_moves(game, io);
+    }
+}
+
+/* Without a difficulty the player is asked for one */
+pub fn run_game(
+    io: &mut dyn Frontend,
+    seed: u64,
+    difficulty: Option<DifficultyLevel>,
+    size: GalaxySize,
+) -> bool {
+    let seed = io.game_seed(seed);
+    let level = io
+        .game_difficulty(difficulty)
+        .unwrap_or_else(|| choose_difficulty(io));
+    let size = io.game_size(size);
+
+    let mut game = Game::initialize(seed, Difficulty::new(level), size);
+
+    begin_mission(&mut game, io);
+
+    play_game(&mut game, io)
+}
+
+/* Carry on with a mission, new or restored, until it is over */
+pub fn play_game(game: &mut Game, io: &mut dyn Frontend) -> bool {
     loop {
-        if game.ship.is_unable_to_navigate() {
-            show_file("assets/startrek.fatal");
+        if check_stranded(game, io) {
+            break;
+        }
+
+        io.refresh(game);
 
-            end_of_time(&mut game);
+        let cmd = io.get_command("Command?");
 
+        execute_command(game, io, &cmd);
+
+        if game.game_over {
             break;
         }
+    }
 
-        let cmd = get_command("Command?");
+    game.exit_flag
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn course(from: (i32, i32), to: (i32, i32)) -> Option<(f32, f32)> {
+        Vec2 {
+            x: from.0,
+            y: from.1,
+        }
+        .course_to(&Vec2 { x: to.0, y: to.1 }, GalaxySize::default())
+    }
+
+    #[test]
+    fn course_to_the_main_headings() {
+        assert_eq!(course((4, 4), (4, 7)), Some((1.0, 0.375)));
+        assert_eq!(course((4, 4), (1, 7)), Some((2.0, 0.375)));
+        assert_eq!(course((4, 4), (1, 4)), Some((3.0, 0.375)));
+        assert_eq!(course((4, 4), (1, 1)), Some((4.0, 0.375)));
+        assert_eq!(course((4, 4), (4, 1)), Some((5.0, 0.375)));
+        assert_eq!(course((4, 4), (7, 1)), Some((6.0, 0.375)));
+        assert_eq!(course((4, 4), (7, 4)), Some((7.0, 0.375)));
+        assert_eq!(course((4, 4), (7, 7)), Some((8.0, 0.375)));
+        assert_eq!(course((4, 4), (4, 4)), None);
+    }
+
+    #[test]
+    fn course_to_in_between() {
+        /* Half way along the side of the square, like the original */
+        assert_eq!(course((4, 4), (2, 8)), Some((1.5, 0.5)));
+        assert_eq!(course((4, 4), (0, 6)), Some((2.5, 0.5)));
+        assert_eq!(course((4, 4), (6, 0)), Some((5.5, 0.5)));
+        assert_eq!(course((4, 4), (7, 6)), Some((7.0 + 2.0 / 3.0, 0.375)));
+        assert_eq!(course((4, 4), (5, 8)), Some((8.75, 0.5)));
+    }
+
+    #[test]
+    fn course_to_across_quadrants() {
+        assert_eq!(course((3, 0), (3, 16)), Some((1.0, 2.0)));
+        assert_eq!(course((60, 60), (3, 3)), Some((4.0, 7.125)));
+
+        let size = GalaxySize::new(8, 8, 10, 10).unwrap();
+        assert_eq!(
+            Vec2 { x: 5, y: 0 }.course_to(&Vec2 { x: 5, y: 15 }, size),
+            Some((1.0, 1.5))
+        );
+    }
 
-        match cmd.as_str() {
-            "nav" => course_control(&mut game),
-            "srs" => short_range_scan(&mut game),
-            "lrs" => long_range_scan(&mut game),
+    /* Steering the course it gives for as many sectors gets there */
+    #[test]
+    fn course_to_leads_there() {
+        let from = Vec2 { x: 20, y: 20 };
 
-            "pha" => phaser_control(&mut game),
-            "tor" => photon_torpedoes(&mut game),
+        for x in 12..=28 {
+            for y in 12..=28 {
+                let to = Vec2 { x, y };
+                let Some((course, _)) = from.course_to(&to, GalaxySize::default()) else {
+                    continue;
+                };
+                let sectors = (x - from.x).abs().max((y - from.y).abs()) as usize;
 
-            "shi" => shield_control(&mut game),
+                assert_eq!(
+                    Track::new(&from, course).nth(sectors - 1),
+                    Some(to),
+                    "course {course}"
+                );
+            }
+        }
+    }
 
-            "dam" => damage_control(&mut game),
-            "com" => library_computer(&mut game),
+    #[test]
+    fn track_follows_the_main_headings() {
+        let from = Vec2 { x: 4, y: 4 };
 
-            "xxx" => resign_commision(&mut game),
+        for (course, step) in COURSE_VECTOR.iter().enumerate() {
+            let track: Vec<Vec2> = Track::new(&from, course as f32 + 1.0).take(3).collect();
 
-            _ => {
-                /* FIXME: showfile ?*/
-                show_file("assets/commands.txt");
+            for (n, p) in track.iter().enumerate() {
+                let n = n as i32 + 1;
+                assert_eq!(
+                    *p,
+                    Vec2 {
+                        x: 4 + step.x * n,
+                        y: 4 + step.y * n
+                    }
+                );
             }
         }
+    }
 
-        if game.exit_flag {
-            break;
+    #[test]
+    fn track_blends_courses_in_between() {
+        let track: Vec<Vec2> = Track::new(&Vec2 { x: 4, y: 0 }, 1.5).take(4).collect();
+
+        assert_eq!(
+            track,
+            [
+                Vec2 { x: 4, y: 1 },
+                Vec2 { x: 3, y: 2 },
+                Vec2 { x: 3, y: 3 },
+                Vec2 { x: 2, y: 4 },
+            ]
+        );
+    }
+
+    /* Whatever the heading nothing in the way can be jumped over */
+    #[test]
+    fn track_steps_one_sector_at_a_time() {
+        for tenths in 10..=90 {
+            let mut last = Vec2 { x: 20, y: 20 };
+
+            for p in Track::new(&last, tenths as f32 / 10.0).take(16) {
+                assert_eq!((p.x - last.x).abs().max((p.y - last.y).abs()), 1);
+                last = p;
+            }
         }
     }
 
-    game.exit_flag
+    #[test]
+    fn warp_energy_is_a_unit_a_sector() {
+        let size = GalaxySize::default();
+
+        assert_eq!(warp_energy(1.0, size), 8);
+        assert_eq!(warp_energy(8.0, size), 64);
+        assert_eq!(warp_energy(0.2, size), 2);
+        assert_eq!(warp_energy(0.1, size), 1);
+        assert_eq!(warp_energy(0.5, GalaxySize::new(8, 8, 10, 10).unwrap()), 5);
+        assert_eq!(warp_energy(1.0, GalaxySize::new(8, 8, 12, 4).unwrap()), 12);
+    }
 }
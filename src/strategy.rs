@@ -0,0 +1,565 @@
+use crate::prelude::*;
+
+/* Klingons a quadrant holds at most, as many as the galaxy starts with */
+pub const MAX_QUADRANT_KLINGONS: u32 = 3;
+
+/* What takes a Klingon out in the galaxy elsewhere */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orders {
+    Reinforce, /* Join the fight the Enterprise is in */
+    Hunt,      /* Go where the Enterprise is */
+    Regroup,   /* Leave a quadrant alone for one with company */
+    Converge,  /* Head for the nearest starbase */
+}
+
+fn nearest_starbase(game: &Game, q: &Vec2) -> Option<Vec2> {
+    game.quadrant_map
+        .iter()
+        .flatten()
+        .filter(|b| b.starbases > 0)
+        .min_by_key(|b| quadrant_distance(q, &b.position))
+        .map(|b| b.position)
+}
+
+/* Quadrants apart, diagonal steps count as one */
+fn quadrant_distance(a: &Vec2, b: &Vec2) -> i32 {
+    (a.x - b.x).abs().max((a.y - b.y).abs())
+}
+
+fn has_room(game: &Game, q: &Vec2) -> bool {
+    game.quadrant_map[q.x as usize][q.y as usize].klingons < MAX_QUADRANT_KLINGONS
+}
+
+/*
+ *	The orders of a Klingon in quadrant `q` and where they take it, None
+ *	to stay. Klingons at a starbase stay to lay siege to it. Commanders
+ *	always hunt the Enterprise, the Super-Commander goes from starbase to
+ *	starbase.
+ */
+pub fn fleet_orders(game: &Game, q: &Vec2, class: KlingonClass) -> Option<(Orders, Vec2)> {
+    let size = game.size;
+    let strategy = &game.difficulty.strategy;
+    let ship = game.ship.get_quadrant_position(size);
+    let quadrant = &game.quadrant_map[q.x as usize][q.y as usize];
+    let distance = quadrant_distance(q, &ship);
+
+    match class {
+        KlingonClass::Commander => return Some((Orders::Hunt, ship)),
+        KlingonClass::SuperCommander if quadrant.starbases > 0 => return None,
+        KlingonClass::SuperCommander => {
+            return nearest_starbase(game, q).map(|b| (Orders::Converge, b));
+        }
+        KlingonClass::Warship => {}
+    }
+
+    if game.get_current_quadrant().klingons > 0 && distance <= strategy.hunt_range * 2 {
+        return Some((Orders::Reinforce, ship));
+    }
+
+    if distance <= strategy.hunt_range {
+        return Some((Orders::Hunt, ship));
+    }
+
+    if quadrant.starbases > 0 {
+        return None;
+    }
+
+    if quadrant.klingons == 1 {
+        let company = (q.x - 1..=q.x + 1)
+            .flat_map(|x| (q.y - 1..=q.y + 1).map(move |y| Vec2 { x, y }))
+            .filter(|n| n != q && size.contains_quadrant(n) && *n != ship)
+            .find(|n| {
+                game.quadrant_map[n.x as usize][n.y as usize].klingons > 0 && has_room(game, n)
+            });
+
+        if let Some(n) = company {
+            return Some((Orders::Regroup, n));
+        }
+    }
+
+    nearest_starbase(game, q).map(|b| (Orders::Converge, b))
+}
+
+/* Take a Klingon to quadrant `to`, if there is room for it */
+fn move_klingon(game: &mut Game, io: &mut dyn Frontend, idx: usize, to: &Vec2) -> bool {
+    let size = game.size;
+
+    if !has_room(game, to) {
+        return false;
+    }
+
+    let from = game.klingons[idx].get_quadrant_position(size);
+    let old_position = game.klingons[idx].position;
+    let new_position = find_empty_place_in_quadrant(&mut game.rng, size, &game.sector_map, to);
+
+    game.get_mut_sector(&old_position).sector_type = SectorType::SPACE;
+    game.get_mut_sector(&new_position).sector_type = game.klingons[idx].class.sector_type();
+    game.klingons[idx].move_to(&new_position);
+
+    game.quadrant_map[from.x as usize][from.y as usize].klingons -= 1;
+    game.quadrant_map[to.x as usize][to.y as usize].klingons += 1;
+
+    if *to == game.ship.get_quadrant_position(size) {
+        io.event(Event::KlingonArrived {
+            class: game.klingons[idx].class,
+            sector: game.klingons[idx].get_sector_position(size),
+        });
+    }
+
+    true
+}
+
+/*
+ *	One stardate of the war. Klingons fighting the Enterprise stay in
+ *	the fight, the others may move a quadrant towards what their orders
+ *	take them to. Commanders and the Super-Commander move every turn,
+ *	the Super-Commander never into the quadrant of the Enterprise.
+ */
+pub fn fleet_turn(game: &mut Game, io: &mut dyn Frontend) {
+    let size = game.size;
+    let ship = game.ship.get_quadrant_position(size);
+
+    for idx in 0..game.klingons.len() {
+        let k = &game.klingons[idx];
+        let q = k.get_quadrant_position(size);
+        let class = k.class;
+
+        if k.destroyed || q == ship {
+            continue;
+        }
+
+        if class == KlingonClass::Warship
+            && get_rand(&mut game.rng, 100) as u32 > game.difficulty.strategy.moves
+        {
+            continue;
+        }
+
+        let Some((_, goal)) = fleet_orders(game, &q, class) else {
+            continue;
+        };
+
+        let step = Vec2 {
+            x: q.x + (goal.x - q.x).signum(),
+            y: q.y + (goal.y - q.y).signum(),
+        };
+
+        if class == KlingonClass::SuperCommander && step == ship {
+            continue;
+        }
+
+        if step != q {
+            move_klingon(game, io, idx, &step);
+        }
+    }
+
+    starbase_sieges(game, io);
+}
+
+/*
+ *	Klingons in a starbase's quadrant lay siege to it and Uhura relays
+ *	its distress call. Unless the Klingons are gone first it falls on the
+ *	stardate it gave, the Enterprise in the quadrant holds them off.
+ */
+pub fn starbase_sieges(game: &mut Game, io: &mut dyn Frontend) {
+    let size = game.size;
+    let ship = game.ship.get_quadrant_position(size);
+    let siege_days = game.difficulty.strategy.siege_days;
+
+    for idx in 0..game.starbases.len() {
+        let starbase = &game.starbases[idx];
+        let q = starbase.get_quadrant_position(size);
+
+        /* A Commander counts for two warships, the Super-Commander three */
+        let klingons: u32 = game
+            .klingons
+            .iter()
+            .filter(|k| !k.destroyed && k.get_quadrant_position(size) == q)
+            .map(|k| k.class.strength())
+            .sum();
+
+        if starbase.destroyed {
+            continue;
+        }
+
+        match starbase.siege {
+            None if klingons > 0 && q != ship => {
+                let falls = game.fleet_stardate + siege_days / klingons as f32;
+
+                game.starbases[idx].siege = Some(falls);
+
+                io.event(Event::StarbaseUnderAttack { quadrant: q, falls });
+            }
+            Some(_) if klingons == 0 => {
+                game.starbases[idx].siege = None;
+
+                io.event(Event::SiegeLifted { quadrant: q });
+            }
+            Some(falls) if q == ship => {
+                game.starbases[idx].siege = Some(falls + 1.0);
+            }
+            Some(falls) if game.fleet_stardate >= falls => {
+                destroy_starbase(game, io, idx);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn destroy_starbase(game: &mut Game, io: &mut dyn Frontend, idx: usize) {
+    let position = game.starbases[idx].position;
+    let q = position.get_quadrant_position(game.size);
+
+    game.starbases[idx].destroyed = true;
+    game.starbases[idx].siege = None;
+    game.get_mut_sector(&position).sector_type = SectorType::SPACE;
+    game.quadrant_map[q.x as usize][q.y as usize].starbases -= 1;
+    game.starbases_left -= 1;
+
+    io.event(Event::StarbaseLost { quadrant: q });
+
+    /* With its last starbase gone the Federation has lost the war */
+    if game.starbases_left == 0 {
+        lose_game(game, io, LossReason::StarbasesLost);
+    }
+}
+
+/* Catch the fleet up with the stardate, a turn for every one gone by */
+pub fn fleet_moves(game: &mut Game, io: &mut dyn Frontend) {
+    while !game.game_over && game.stardate - game.fleet_stardate >= 1.0 {
+        game.fleet_stardate += 1.0;
+
+        fleet_turn(game, io);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /*
+     *	A Novice galaxy with a warship brought to a starbase away from the
+     *	Enterprise, no other starbase has Klingons at it. Returns the starbase, the
+     *	warship and the quadrant it came from.
+     */
+    fn besieged() -> (Game, usize, usize, Vec2) {
+        for seed in 1.. {
+            let mut game = Game::initialize(
+                seed,
+                Difficulty::new(DifficultyLevel::Novice),
+                GalaxySize::default(),
+            );
+            let size = game.size;
+            let ship = game.ship.get_quadrant_position(size);
+
+            /* No other siege to get in the way */
+            if game.starbases.iter().any(|b| {
+                let q = b.get_quadrant_position(size);
+                game.quadrant_map[q.x as usize][q.y as usize].klingons > 0
+            }) {
+                continue;
+            }
+
+            let Some(base) = game
+                .starbases
+                .iter()
+                .position(|b| b.get_quadrant_position(size) != ship)
+            else {
+                continue;
+            };
+
+            let Some(warship) = game.klingons.iter().position(|k| {
+                k.class == KlingonClass::Warship
+                    && k.get_quadrant_position(size) != ship
+                    && quadrant_distance(&k.get_quadrant_position(size), &ship) > 1
+            }) else {
+                continue;
+            };
+
+            let q = game.starbases[base].get_quadrant_position(size);
+            let from = game.klingons[warship].get_quadrant_position(size);
+
+            assert!(move_klingon(
+                &mut game,
+                &mut ScriptedFrontend::default(),
+                warship,
+                &q
+            ));
+
+            return (game, base, warship, from);
+        }
+
+        unreachable!()
+    }
+
+    fn sieges(game: &mut Game) -> Vec<Event> {
+        let mut io = ScriptedFrontend::default();
+
+        starbase_sieges(game, &mut io);
+        io.into_events()
+    }
+
+    #[test]
+    fn siege_falls_on_its_stardate() {
+        let (mut game, base, _, _) = besieged();
+        let q = game.starbases[base].get_quadrant_position(game.size);
+        let position = game.starbases[base].position;
+        let starbases = game.starbases_left;
+
+        let falls = game.fleet_stardate + game.difficulty.strategy.siege_days;
+        assert!(sieges(&mut game).contains(&Event::StarbaseUnderAttack { quadrant: q, falls }));
+        assert_eq!(game.starbases[base].siege, Some(falls));
+
+        game.fleet_stardate = falls - 0.5;
+        sieges(&mut game);
+        assert!(!game.starbases[base].destroyed);
+
+        game.fleet_stardate = falls;
+        assert!(sieges(&mut game).contains(&Event::StarbaseLost { quadrant: q }));
+        assert!(game.starbases[base].destroyed);
+        assert_eq!(game.starbases_left, starbases - 1);
+        assert_eq!(game.quadrant_map[q.x as usize][q.y as usize].starbases, 0);
+        assert_eq!(game.get_sector(&position).sector_type, SectorType::SPACE);
+        assert!(!game.game_over);
+    }
+
+    #[test]
+    fn siege_is_lifted_when_the_klingons_leave() {
+        let (mut game, base, warship, from) = besieged();
+        let q = game.starbases[base].get_quadrant_position(game.size);
+
+        sieges(&mut game);
+        assert!(game.starbases[base].siege.is_some());
+
+        assert!(move_klingon(
+            &mut game,
+            &mut ScriptedFrontend::default(),
+            warship,
+            &from
+        ));
+        assert!(sieges(&mut game).contains(&Event::SiegeLifted { quadrant: q }));
+        assert_eq!(game.starbases[base].siege, None);
+
+        game.fleet_stardate += game.difficulty.strategy.siege_days;
+        sieges(&mut game);
+        assert!(!game.starbases[base].destroyed);
+    }
+
+    #[test]
+    fn enterprise_holds_the_klingons_off() {
+        let (mut game, base, _, _) = besieged();
+        let size = game.size;
+        let q = game.starbases[base].get_quadrant_position(size);
+
+        sieges(&mut game);
+        let falls = game.starbases[base].siege.unwrap();
+
+        let sector = find_empty_place_in_quadrant(&mut game.rng, size, &game.sector_map, &q);
+        game.ship.move_to(&sector);
+
+        game.fleet_stardate = falls;
+        sieges(&mut game);
+        assert!(!game.starbases[base].destroyed);
+        assert_eq!(game.starbases[base].siege, Some(falls + 1.0));
+    }
+
+    #[test]
+    fn losing_the_last_starbase_loses_the_game() {
+        let (mut game, base, _, _) = besieged();
+
+        for (idx, starbase) in game.starbases.iter_mut().enumerate() {
+            starbase.destroyed = idx != base;
+        }
+        game.starbases_left = 1;
+
+        sieges(&mut game);
+        game.fleet_stardate = game.starbases[base].siege.unwrap();
+
+        let events = sieges(&mut game);
+        assert!(game.game_over);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::GameLost {
+                reason: LossReason::StarbasesLost,
+                ..
+            }
+        )));
+    }
+
+    /*
+     *	A Good galaxy with nothing in it as far as the orders go, the
+     *	Enterprise in the top left quadrant
+     */
+    fn empty_galaxy() -> Game {
+        let mut game = Game::initialize(
+            1,
+            Difficulty::new(DifficultyLevel::Good),
+            GalaxySize::default(),
+        );
+
+        for quadrant in game.quadrant_map.iter_mut().flatten() {
+            quadrant.klingons = 0;
+            quadrant.starbases = 0;
+        }
+
+        game.ship.move_to(&Vec2 { x: 0, y: 0 });
+        game
+    }
+
+    fn put(game: &mut Game, x: i32, y: i32, klingons: u32, starbases: u32) -> Vec2 {
+        let quadrant = &mut game.quadrant_map[x as usize][y as usize];
+
+        quadrant.klingons = klingons;
+        quadrant.starbases = starbases;
+        Vec2 { x, y }
+    }
+
+    #[test]
+    fn klingons_near_the_enterprise_hunt_it() {
+        let mut game = empty_galaxy();
+        let ship = Vec2 { x: 0, y: 0 };
+        let q = put(&mut game, 2, 2, 1, 0);
+
+        put(&mut game, 7, 7, 0, 1);
+
+        assert_eq!(
+            fleet_orders(&game, &q, KlingonClass::Warship),
+            Some((Orders::Hunt, ship))
+        );
+    }
+
+    #[test]
+    fn klingons_reinforce_a_fight_from_further() {
+        let mut game = empty_galaxy();
+        let ship = Vec2 { x: 0, y: 0 };
+        let q = put(&mut game, 4, 4, 2, 0);
+        let base = put(&mut game, 6, 6, 0, 1);
+
+        assert_eq!(
+            fleet_orders(&game, &q, KlingonClass::Warship),
+            Some((Orders::Converge, base))
+        );
+
+        put(&mut game, 0, 0, 1, 0);
+        assert_eq!(
+            fleet_orders(&game, &q, KlingonClass::Warship),
+            Some((Orders::Reinforce, ship))
+        );
+
+        let far = put(&mut game, 5, 5, 2, 0);
+        assert_eq!(
+            fleet_orders(&game, &far, KlingonClass::Warship),
+            Some((Orders::Converge, base))
+        );
+    }
+
+    #[test]
+    fn lone_klingons_regroup_where_there_is_room() {
+        let mut game = empty_galaxy();
+        let q = put(&mut game, 5, 5, 1, 0);
+        let company = put(&mut game, 6, 6, 1, 0);
+        let base = put(&mut game, 7, 3, 0, 1);
+
+        assert_eq!(
+            fleet_orders(&game, &q, KlingonClass::Warship),
+            Some((Orders::Regroup, company))
+        );
+
+        put(&mut game, 6, 6, MAX_QUADRANT_KLINGONS, 0);
+        assert_eq!(
+            fleet_orders(&game, &q, KlingonClass::Warship),
+            Some((Orders::Converge, base))
+        );
+    }
+
+    #[test]
+    fn klingons_converge_on_the_nearest_starbase_and_stay() {
+        let mut game = empty_galaxy();
+        let q = put(&mut game, 5, 5, 2, 0);
+        let near = put(&mut game, 7, 3, 0, 1);
+
+        put(&mut game, 2, 7, 0, 1);
+
+        assert_eq!(
+            fleet_orders(&game, &q, KlingonClass::Warship),
+            Some((Orders::Converge, near))
+        );
+        assert_eq!(fleet_orders(&game, &near, KlingonClass::Warship), None);
+
+        /* With no starbase left there is nowhere to go */
+        let mut game = empty_galaxy();
+        let q = put(&mut game, 5, 5, 2, 0);
+
+        assert_eq!(fleet_orders(&game, &q, KlingonClass::Warship), None);
+    }
+
+    #[test]
+    fn commanders_hunt_and_the_super_commander_converges() {
+        let mut game = empty_galaxy();
+        let ship = Vec2 { x: 0, y: 0 };
+        let q = put(&mut game, 7, 7, 1, 0);
+        let base = put(&mut game, 5, 6, 0, 1);
+
+        assert_eq!(
+            fleet_orders(&game, &q, KlingonClass::Commander),
+            Some((Orders::Hunt, ship))
+        );
+        assert_eq!(
+            fleet_orders(&game, &base, KlingonClass::Commander),
+            Some((Orders::Hunt, ship))
+        );
+        assert_eq!(
+            fleet_orders(&game, &q, KlingonClass::SuperCommander),
+            Some((Orders::Converge, base))
+        );
+        assert_eq!(
+            fleet_orders(&game, &base, KlingonClass::SuperCommander),
+            None
+        );
+    }
+
+    /* Many stardates of war keep the quadrant counts right and in bounds */
+    #[test]
+    fn fleet_keeps_the_quadrant_counts() {
+        for level in [
+            DifficultyLevel::Novice,
+            DifficultyLevel::Good,
+            DifficultyLevel::Expert,
+            DifficultyLevel::Emeritus,
+        ] {
+            for seed in 1..10 {
+                let mut game =
+                    Game::initialize(seed, Difficulty::new(level), GalaxySize::default());
+                let mut io = ScriptedFrontend::default();
+                let size = game.size;
+
+                for _ in 0..100 {
+                    game.fleet_stardate += 1.0;
+                    fleet_turn(&mut game, &mut io);
+
+                    for quadrant in game.quadrant_map.iter().flatten() {
+                        let klingons = game
+                            .klingons
+                            .iter()
+                            .filter(|k| {
+                                !k.destroyed && k.get_quadrant_position(size) == quadrant.position
+                            })
+                            .count();
+
+                        assert!(quadrant.klingons <= MAX_QUADRANT_KLINGONS);
+                        assert_eq!(
+                            quadrant.klingons as usize, klingons,
+                            "{level:?} seed {seed}"
+                        );
+                    }
+
+                    for k in game.klingons.iter().filter(|k| !k.destroyed) {
+                        assert_eq!(
+                            game.get_sector(&k.position).sector_type,
+                            k.class.sector_type()
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use crate::prelude::*;
+
+/* What a Klingon is after this turn */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intent {
+    Attack,
+    Retreat,
+}
+
+const NEIGHBOURS: [Vec2; 8] = [
+    Vec2 { x: -1, y: -1 },
+    Vec2 { x: -1, y: 0 },
+    Vec2 { x: -1, y: 1 },
+    Vec2 { x: 0, y: -1 },
+    Vec2 { x: 0, y: 1 },
+    Vec2 { x: 1, y: -1 },
+    Vec2 { x: 1, y: 0 },
+    Vec2 { x: 1, y: 1 },
+];
+
+/* Worth this many sectors of distance to a retreating Klingon */
+const COVER_BONUS: f32 = 3.0;
+
+/* Weak warships back off, the rest and all Commanders close in */
+pub fn klingon_intent(difficulty: &Difficulty, k: &Klingon) -> Intent {
+    let tactics = &difficulty.tactics;
+
+    if k.class == KlingonClass::Warship
+        && k.energy.units() * 100 < tactics.retreat_below * difficulty.klingon_energy
+    {
+        Intent::Retreat
+    } else {
+        Intent::Attack
+    }
+}
+
+fn distance(a: &Vec2, b: &Vec2) -> f32 {
+    (((a.x - b.x) * (a.x - b.x) + (a.y - b.y) * (a.y - b.y)) as f32).sqrt()
+}
+
+/* Unit vector from the Enterprise to a position */
+fn bearing(from: &Vec2, to: &Vec2) -> (f32, f32) {
+    let d = distance(from, to).max(1.0);
+
+    ((to.x - from.x) as f32 / d, (to.y - from.y) as f32 / d)
+}
+
+/* A star on the straight line between two positions */
+pub fn is_covered(game: &Game, from: &Vec2, to: &Vec2) -> bool {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    let steps = dx.abs().max(dy.abs());
+
+    (1..steps).any(|i| {
+        let p = Vec2 {
+            x: from.x + (dx as f32 * i as f32 / steps as f32).round() as i32,
+            y: from.y + (dy as f32 * i as f32 / steps as f32).round() as i32,
+        };
+
+        game.get_sector(&p).sector_type == SectorType::STAR
+    })
+}
+
+/*
+ *	How much a Klingon likes a sector, lower is better. Attackers want to
+ *	be at their range from the Enterprise and, when flanking, away from
+ *	the bearings the others took. Retreating ones want distance and the
+ *	cover of a star.
+ */
+fn sector_score(game: &Game, intent: Intent, position: &Vec2, taken: &[(f32, f32)]) -> f32 {
+    let tactics = &game.difficulty.tactics;
+    let ship = &game.ship.position;
+    let d = distance(ship, position);
+
+    match intent {
+        Intent::Attack => {
+            let mut score = (d - tactics.range).abs();
+
+            if tactics.flank {
+                let (bx, by) = bearing(ship, position);
+
+                /* 1 on top of another Klingon's bearing, -1 opposite it */
+                score += taken
+                    .iter()
+                    .map(|(tx, ty)| bx * tx + by * ty)
+                    .fold(-1.0, f32::max);
+            }
+
+            score
+        }
+        Intent::Retreat => {
+            if tactics.cover && is_covered(game, ship, position) {
+                -d - COVER_BONUS
+            } else {
+                -d
+            }
+        }
+    }
+}
+
+/*
+ *	Walk a Klingon through the empty sectors of its quadrant, one sector
+ *	at a time up to the speed its difficulty allows, for as long as each
+ *	step improves its position. `taken` holds the bearings the Klingons
+ *	that already moved ended up on, this one's is added.
+ */
+pub fn tactical_move(game: &mut Game, idx: usize, taken: &mut Vec<(f32, f32)>) {
+    let size = game.size;
+    let intent = klingon_intent(&game.difficulty, &game.klingons[idx]);
+    let quadrant = game.klingons[idx].get_quadrant_position(size);
+    let start = game.klingons[idx].position;
+    let class = game.klingons[idx].class;
+
+    let mut position = start;
+    let mut score = sector_score(game, intent, &position, taken);
+
+    for _ in 0..game.difficulty.tactics.speed + class.extra_speed() {
+        let sector = Vec2 {
+            x: position.x - quadrant.x * size.quadrant_height as i32,
+            y: position.y - quadrant.y * size.quadrant_width as i32,
+        };
+
+        let best = NEIGHBOURS
+            .iter()
+            .map(|n| Vec2 {
+                x: sector.x + n.x,
+                y: sector.y + n.y,
+            })
+            .filter(|s| size.contains_sector(s))
+            .map(|s| size.position(&quadrant, &s))
+            .filter(|p| game.get_sector(p).sector_type == SectorType::SPACE)
+            .map(|p| (sector_score(game, intent, &p, taken), p))
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        match best {
+            Some((s, p)) if s < score => {
+                score = s;
+                position = p;
+            }
+            _ => break,
+        }
+    }
+
+    taken.push(bearing(&game.ship.position, &position));
+
+    if position != start {
+        game.get_mut_sector(&start).sector_type = SectorType::SPACE;
+        game.get_mut_sector(&position).sector_type = class.sector_type();
+        game.klingons[idx].move_to(&position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVELS: [DifficultyLevel; 4] = [
+        DifficultyLevel::Novice,
+        DifficultyLevel::Good,
+        DifficultyLevel::Expert,
+        DifficultyLevel::Emeritus,
+    ];
+
+    /*
+     *	The quadrant of the Enterprise emptied but for the ship and one
+     *	warship, at sectors `ship` and `klingon`. Returns the warship.
+     */
+    fn arena(level: DifficultyLevel, seed: u64, ship: Vec2, klingon: Vec2) -> (Game, usize) {
+        let mut game = Game::initialize(seed, Difficulty::new(level), GalaxySize::default());
+        let size = game.size;
+        let q = game.ship.get_quadrant_position(size);
+
+        for idx in game.get_klingons_idxs_in_current_quadrant() {
+            game.klingons[idx].destroyed = true;
+        }
+
+        for x in 0..size.quadrant_height as i32 {
+            for y in 0..size.quadrant_width as i32 {
+                game.get_mut_sector(&size.position(&q, &Vec2 { x, y }))
+                    .sector_type = SectorType::SPACE;
+            }
+        }
+
+        let ship = size.position(&q, &ship);
+        game.ship.move_to(&ship);
+        game.get_mut_sector(&ship).sector_type = SectorType::SHIP;
+
+        let position = size.position(&q, &klingon);
+        game.get_mut_sector(&position).sector_type = SectorType::KLINGON;
+        game.klingons.push(Klingon {
+            position,
+            energy: Energy::new(game.difficulty.klingon_energy),
+            destroyed: false,
+            class: KlingonClass::Warship,
+        });
+        game.quadrant_map[q.x as usize][q.y as usize].klingons = 1;
+
+        let idx = game.klingons.len() - 1;
+        (game, idx)
+    }
+
+    /* Energy a warship backs off with at its level, none at Novice */
+    fn weak(game: &Game) -> Energy {
+        let d = &game.difficulty;
+
+        Energy::new((d.klingon_energy * d.tactics.retreat_below / 100).saturating_sub(1))
+    }
+
+    fn star(game: &mut Game, sector: Vec2) {
+        let size = game.size;
+        let q = game.ship.get_quadrant_position(size);
+
+        game.get_mut_sector(&size.position(&q, &sector)).sector_type = SectorType::STAR;
+    }
+
+    fn sectors_moved(a: &Vec2, b: &Vec2) -> u32 {
+        (a.x - b.x).abs().max((a.y - b.y).abs()) as u32
+    }
+
+    #[test]
+    fn klingons_move_at_most_their_speed() {
+        for level in LEVELS {
+            for class in [KlingonClass::Warship, KlingonClass::Commander] {
+                let (mut game, idx) = arena(level, 1, Vec2 { x: 0, y: 0 }, Vec2 { x: 7, y: 7 });
+                game.klingons[idx].class = class;
+
+                let start = game.klingons[idx].position;
+                tactical_move(&mut game, idx, &mut Vec::new());
+
+                /* From across the quadrant every step closes in */
+                assert_eq!(
+                    sectors_moved(&start, &game.klingons[idx].position),
+                    game.difficulty.tactics.speed + class.extra_speed(),
+                    "{level:?} {class:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn klingons_enter_only_empty_sectors() {
+        for level in LEVELS {
+            for seed in 1..20 {
+                let (mut game, idx) = arena(level, seed, Vec2 { x: 3, y: 4 }, Vec2 { x: 0, y: 0 });
+                let size = game.size;
+                let q = game.ship.get_quadrant_position(size);
+
+                for x in 0..size.quadrant_height as i32 {
+                    for y in 0..size.quadrant_width as i32 {
+                        let p = size.position(&q, &Vec2 { x, y });
+
+                        if game.get_sector(&p).sector_type == SectorType::SPACE
+                            && get_rand(&mut game.rng, 100) <= 30
+                        {
+                            star(&mut game, Vec2 { x, y });
+                        }
+                    }
+                }
+
+                for turn in 0..6 {
+                    /* Closing in, then backing off */
+                    if turn == 3 {
+                        game.klingons[idx].energy = weak(&game);
+                    }
+
+                    let before = game.sector_map.clone();
+                    let start = game.klingons[idx].position;
+
+                    tactical_move(&mut game, idx, &mut Vec::new());
+
+                    let end = game.klingons[idx].position;
+                    if end != start {
+                        assert_eq!(
+                            before[end.x as usize][end.y as usize].sector_type,
+                            SectorType::SPACE,
+                            "{level:?} seed {seed}"
+                        );
+                        assert_eq!(game.get_sector(&start).sector_type, SectorType::SPACE);
+                        assert_eq!(game.get_sector(&end).sector_type, SectorType::KLINGON);
+                    }
+                    assert!(sectors_moved(&start, &end) <= game.difficulty.tactics.speed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn boxed_in_klingons_stay() {
+        let (mut game, idx) = arena(
+            DifficultyLevel::Emeritus,
+            1,
+            Vec2 { x: 0, y: 0 },
+            Vec2 { x: 6, y: 6 },
+        );
+        let start = game.klingons[idx].position;
+
+        for n in NEIGHBOURS {
+            star(
+                &mut game,
+                Vec2 {
+                    x: 6 + n.x,
+                    y: 6 + n.y,
+                },
+            );
+        }
+
+        tactical_move(&mut game, idx, &mut Vec::new());
+        assert_eq!(game.klingons[idx].position, start);
+    }
+
+    #[test]
+    fn klingons_retreat_below_their_energy_threshold() {
+        for level in [
+            DifficultyLevel::Good,
+            DifficultyLevel::Expert,
+            DifficultyLevel::Emeritus,
+        ] {
+            let (mut game, idx) = arena(level, 1, Vec2 { x: 0, y: 0 }, Vec2 { x: 4, y: 4 });
+            let ship = game.ship.position;
+            let d = &game.difficulty;
+
+            game.klingons[idx].energy =
+                Energy::new(d.klingon_energy * d.tactics.retreat_below / 100);
+            assert_eq!(
+                klingon_intent(&game.difficulty, &game.klingons[idx]),
+                Intent::Attack
+            );
+
+            game.klingons[idx].energy = weak(&game);
+            assert_eq!(
+                klingon_intent(&game.difficulty, &game.klingons[idx]),
+                Intent::Retreat
+            );
+
+            let before = distance(&ship, &game.klingons[idx].position);
+            tactical_move(&mut game, idx, &mut Vec::new());
+            assert!(
+                distance(&ship, &game.klingons[idx].position) > before,
+                "{level:?}"
+            );
+
+            /* Commanders never back off */
+            game.klingons[idx].class = KlingonClass::Commander;
+            assert_eq!(
+                klingon_intent(&game.difficulty, &game.klingons[idx]),
+                Intent::Attack
+            );
+        }
+
+        /* At Novice nothing is weak enough */
+        let (mut game, idx) = arena(
+            DifficultyLevel::Novice,
+            1,
+            Vec2 { x: 0, y: 0 },
+            Vec2 { x: 4, y: 4 },
+        );
+        game.klingons[idx].energy = Energy::new(0);
+        assert_eq!(
+            klingon_intent(&game.difficulty, &game.klingons[idx]),
+            Intent::Attack
+        );
+    }
+
+    /*
+     *	Backing off from the corner of the Enterprise, the farthest way is
+     *	along the edge. From Expert on the Klingon takes the sector behind
+     *	the star at 5,3 instead.
+     */
+    #[test]
+    fn klingons_take_cover_from_expert_on() {
+        for level in [
+            DifficultyLevel::Good,
+            DifficultyLevel::Expert,
+            DifficultyLevel::Emeritus,
+        ] {
+            let (mut game, idx) = arena(level, 1, Vec2 { x: 0, y: 0 }, Vec2 { x: 7, y: 5 });
+            let ship = game.ship.position;
+            let cover = level != DifficultyLevel::Good;
+
+            star(&mut game, Vec2 { x: 5, y: 3 });
+            game.klingons[idx].energy = weak(&game);
+
+            assert_eq!(game.difficulty.tactics.cover, cover);
+
+            tactical_move(&mut game, idx, &mut Vec::new());
+            assert_eq!(
+                is_covered(&game, &ship, &game.klingons[idx].position),
+                cover,
+                "{level:?}"
+            );
+        }
+    }
+}
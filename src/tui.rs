@@ -0,0 +1,501 @@
+use ratatui::crossterm::event::{self, Event as TermEvent, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Position};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use rustyline::history::{FileHistory, History, SearchDirection};
+
+use crate::prelude::*;
+
+/* How much of the message log is kept for scrolling back */
+const LOG_LINES: usize = 1000;
+
+const PAGE: usize = 10;
+
+/* What the sensor, status and record panes show, taken from the game
+before every command */
+struct Panes {
+    sectors: Vec<Vec<SectorType>>,
+    sensors_out: bool,
+    condition: &'static str,
+    status: Vec<(&'static str, String)>,
+    record: Vec<Vec<Option<String>>>,
+    quadrant: Vec2,
+}
+
+impl Panes {
+    fn new(game: &Game) -> Self {
+        let quadrant = game.ship.get_quadrant_position(game.size);
+        let sector = game.ship.get_sector_position(game.size);
+
+        let sectors = (0..game.size.quadrant_height as i32)
+            .map(|i| {
+                (0..game.size.quadrant_width as i32)
+                    .map(|j| {
+                        let p = game.size.position(&quadrant, &Vec2 { x: i, y: j });
+
+                        game.sector_map[p.x as usize][p.y as usize].sector_type
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let record = game
+            .quadrant_map
+            .iter()
+            .map(|row| row.iter().map(|q| q.record.clone()).collect())
+            .collect();
+
+        Panes {
+            sectors,
+            sensors_out: game
+                .ship
+                .get_device(DeviceType::ShortRangeSensors)
+                .is_damaged(),
+            condition: game.condition(),
+            status: vec![
+                ("Stardate", format!("{:.2}", game.stardate)),
+                ("Condition", game.condition().to_string()),
+                (
+                    "Quadrant",
+                    format!("{}, {}", quadrant.x + 1, quadrant.y + 1),
+                ),
+                ("Sector", format!("{}, {}", sector.x + 1, sector.y + 1)),
+                ("Torpedoes", game.ship.torps.to_string()),
+                ("Energy", game.ship.get_total_energy().to_string()),
+                ("Shields", game.ship.shield.to_string()),
+                ("Klingons", game.klingons_left.to_string()),
+            ],
+            record,
+            quadrant,
+        }
+    }
+}
+
+/*
+ *	Full screen frontend: the short range sensors, the ship status and
+ *	the galactic record stay on screen above a scrolling message log,
+ *	answers are typed on the prompt line at the bottom. PageUp and
+ *	PageDown scroll the log, Esc cancels a prompt and Ctrl-C quits.
+ *	Commands share their history and completion with the console.
+ */
+pub struct TuiFrontend {
+    terminal: DefaultTerminal,
+    panes: Option<Panes>,
+    log: Vec<String>,
+    scroll: usize,
+    history: FileHistory,
+}
+
+impl TuiFrontend {
+    pub fn new() -> Self {
+        let mut history = FileHistory::new();
+
+        /* No history yet is fine */
+        let _ = history.set_max_len(HISTORY_SIZE);
+        let _ = history.load(&history_path());
+
+        TuiFrontend {
+            terminal: ratatui::init(),
+            panes: None,
+            log: vec![String::new()],
+            scroll: 0,
+            history,
+        }
+    }
+
+    fn draw(&mut self, prompt: &str, input: &str) {
+        let TuiFrontend {
+            terminal,
+            panes,
+            log,
+            scroll,
+            ..
+        } = self;
+
+        let _ = terminal.draw(|frame| render(frame, panes.as_ref(), log, *scroll, prompt, input));
+    }
+
+    /* Wait for a key press, None if the screen just needs redrawing */
+    fn read_key(&mut self) -> Option<KeyCode> {
+        match event::read() {
+            Ok(TermEvent::Key(key)) if key.kind == KeyEventKind::Press => {
+                if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                    ratatui::restore();
+                    std::process::exit(130);
+                }
+
+                match key.code {
+                    KeyCode::PageUp => {
+                        self.scroll = (self.scroll + PAGE).min(self.log.len());
+                        None
+                    }
+                    KeyCode::PageDown => {
+                        self.scroll = self.scroll.saturating_sub(PAGE);
+                        None
+                    }
+                    code => Some(code),
+                }
+            }
+            Ok(_) => None,
+            Err(_) => {
+                /* The terminal is gone, nobody left at the helm */
+                ratatui::restore();
+                std::process::exit(1);
+            }
+        }
+    }
+
+    /* Read a line typed at the prompt, None if the player pressed Esc.
+    Commands can be recalled with Up and Down and completed with Tab */
+    fn read_line(&mut self, prompt: &str, command: bool) -> Option<String> {
+        let mut input = String::new();
+        let mut recalled = self.history.len();
+
+        loop {
+            self.draw(prompt, &input);
+
+            match self.read_key() {
+                Some(KeyCode::Enter) => break,
+                Some(KeyCode::Esc) => {
+                    self.println(prompt);
+                    return None;
+                }
+                Some(KeyCode::Backspace) => {
+                    input.pop();
+                }
+                Some(KeyCode::Char(c)) => input.push(c),
+                Some(KeyCode::Up) if command && recalled > 0 => {
+                    recalled -= 1;
+                    input = self.recall(recalled);
+                }
+                Some(KeyCode::Down) if command && recalled < self.history.len() => {
+                    recalled += 1;
+                    input = self.recall(recalled);
+                }
+                Some(KeyCode::Tab) if command => self.complete(&mut input),
+                _ => {}
+            }
+        }
+
+        let input = input.trim().to_string();
+
+        self.println(&format!("{prompt} {input}"));
+
+        if command && !input.is_empty() && self.history.add(&input).unwrap_or(false) {
+            let path = history_path();
+
+            if let Some(dir) = path.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+
+            let _ = self.history.append(&path);
+        }
+
+        Some(input)
+    }
+
+    /* A command from the history, past the newest one is an empty line */
+    fn recall(&self, index: usize) -> String {
+        match self.history.get(index, SearchDirection::Forward) {
+            Ok(Some(entry)) => entry.entry.into_owned(),
+            _ => String::new(),
+        }
+    }
+
+    /* Complete the word being typed as far as the candidates agree,
+    listing them in the log when there is more than one */
+    fn complete(&mut self, input: &mut String) {
+        let (start, candidates) = complete_command(input, input.len());
+
+        let Some((first, _)) = candidates.first() else {
+            return;
+        };
+
+        let common = candidates.iter().fold(first.clone(), |common, (c, _)| {
+            common
+                .chars()
+                .zip(c.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        });
+
+        input.replace_range(start.., &common);
+
+        if candidates.len() == 1 {
+            input.push(' ');
+        } else {
+            let shown: Vec<&str> = candidates.iter().map(|(_, d)| d.as_str()).collect();
+            self.println(&shown.join("   "));
+        }
+    }
+
+    /* Esc or an empty answer cancels */
+    fn read_number<T>(&mut self, message: &str, min: T, max: T) -> Option<T>
+    where
+        T: std::str::FromStr + PartialOrd + std::fmt::Display + Copy,
+    {
+        loop {
+            match check_number(&self.read_line(message, false)?, min, max) {
+                Ok(v) => return v,
+                Err(e) => self.println(&e),
+            }
+        }
+    }
+}
+
+impl Default for TuiFrontend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TuiFrontend {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
+impl Frontend for TuiFrontend {
+    fn print(&mut self, text: &str) {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.log.push(String::new());
+            }
+
+            if let Some(line) = self.log.last_mut() {
+                line.push_str(part);
+            }
+        }
+
+        if self.log.len() > LOG_LINES {
+            self.log.drain(..self.log.len() - LOG_LINES);
+        }
+
+        self.scroll = 0;
+    }
+
+    fn refresh(&mut self, game: &Game) {
+        self.panes = Some(Panes::new(game));
+    }
+
+    fn has_status_display(&self) -> bool {
+        true
+    }
+
+    fn any_key(&mut self, message: &str) {
+        loop {
+            self.draw(message, "");
+
+            if self.read_key().is_some() {
+                break;
+            }
+        }
+    }
+
+    fn get_command(&mut self, message: &str) -> String {
+        self.read_line(message, true).unwrap_or_default()
+    }
+
+    fn input_string(&mut self, message: &str) -> String {
+        self.read_line(message, false).unwrap_or_default()
+    }
+
+    fn yesno(&mut self, message: &str, default: bool) -> bool {
+        let prompt = format!("{message} {}", if default { "(Y/n)" } else { "(y/N)" });
+
+        let answer = loop {
+            self.draw(&prompt, "");
+
+            match self.read_key() {
+                Some(KeyCode::Char('y' | 'Y')) => break true,
+                Some(KeyCode::Char('n' | 'N')) => break false,
+                Some(KeyCode::Enter | KeyCode::Esc) => break default,
+                _ => {}
+            }
+        };
+
+        self.println(&format!("{prompt} {}", if answer { "Yes" } else { "No" }));
+
+        answer
+    }
+
+    fn input_f32(&mut self, message: &str, min: f32, max: f32) -> Option<f32> {
+        self.read_number(message, min, max)
+    }
+
+    fn input_i32(&mut self, message: &str, min: i32, max: i32) -> Option<i32> {
+        self.read_number(message, min, max)
+    }
+}
+
+fn sector_style(sector_type: SectorType) -> Style {
+    match sector_type {
+        SectorType::SHIP => Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        SectorType::KLINGON | SectorType::COMMANDER => {
+            Style::new().fg(Color::Red).add_modifier(Modifier::BOLD)
+        }
+        SectorType::SUPERCOMMANDER => Style::new().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+        SectorType::BASE => Style::new().fg(Color::Green),
+        _ => Style::new(),
+    }
+}
+
+fn condition_style(condition: &str) -> Style {
+    match condition {
+        "*RED*" => Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+        "YELLOW" => Style::new().fg(Color::Yellow),
+        "DOCKED" => Style::new().fg(Color::Cyan),
+        _ => Style::new().fg(Color::Green),
+    }
+}
+
+fn render(
+    frame: &mut Frame,
+    panes: Option<&Panes>,
+    log: &[String],
+    scroll: usize,
+    prompt: &str,
+    input: &str,
+) {
+    let area = frame.area();
+
+    /* The original game was laid out for a MAXCOL x MAXROW screen */
+    if area.width < MAXCOL as u16 || area.height < MAXROW as u16 {
+        frame.render_widget(
+            Paragraph::new(format!(
+                "Please make the terminal at least {MAXCOL}x{MAXROW} characters, it is {}x{}",
+                area.width, area.height
+            ))
+            .wrap(Wrap { trim: true }),
+            area,
+        );
+        return;
+    }
+
+    /* The panes grow with the galaxy, borders take two lines and columns */
+    let (rows, sensor_width, record_width) = match panes {
+        Some(panes) => (
+            panes
+                .sectors
+                .len()
+                .max(panes.record.len())
+                .max(panes.status.len()),
+            3 * panes.sectors.first().map_or(0, Vec::len),
+            4 * panes.record.first().map_or(0, Vec::len),
+        ),
+        None => (8, 24, 32),
+    };
+
+    let [top, log_area, prompt_area] = Layout::vertical([
+        Constraint::Length(rows as u16 + 2),
+        Constraint::Min(0),
+        Constraint::Length(3),
+    ])
+    .areas(area);
+
+    let [sensor_area, status_area, record_area] = Layout::horizontal([
+        Constraint::Length(sensor_width as u16 + 2),
+        Constraint::Length(21),
+        Constraint::Min(record_width as u16 + 1),
+    ])
+    .areas(top);
+
+    let mut sensors = Vec::new();
+    let mut status = Vec::new();
+    let mut record = Vec::new();
+
+    if let Some(panes) = panes {
+        if panes.sensors_out {
+            sensors.push(Line::from("Sensors are out"));
+        } else {
+            for row in &panes.sectors {
+                sensors.push(Line::from(
+                    row.iter()
+                        .map(|&s| Span::styled(s.glyph(), sector_style(s)))
+                        .collect::<Vec<_>>(),
+                ));
+            }
+        }
+
+        for (label, value) in &panes.status {
+            let style = if *label == "Condition" {
+                condition_style(panes.condition)
+            } else {
+                Style::new()
+            };
+
+            status.push(Line::from(vec![
+                Span::raw(format!(" {label:<11}")),
+                Span::styled(value.clone(), style),
+            ]));
+        }
+
+        for (i, row) in panes.record.iter().enumerate() {
+            let mut spans = Vec::new();
+
+            for (j, q) in row.iter().enumerate() {
+                let style = if panes.quadrant.x == i as i32 && panes.quadrant.y == j as i32 {
+                    Style::new().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::new()
+                };
+
+                if j > 0 {
+                    spans.push(Span::raw(" "));
+                }
+                spans.push(Span::styled(q.as_deref().unwrap_or("***"), style));
+            }
+
+            record.push(Line::from(spans));
+        }
+    }
+
+    frame.render_widget(
+        Paragraph::new(sensors).block(Block::bordered().title("Short Range Scan")),
+        sensor_area,
+    );
+    frame.render_widget(
+        Paragraph::new(status).block(Block::bordered().title("Status")),
+        status_area,
+    );
+    frame.render_widget(
+        Paragraph::new(record).block(Block::bordered().title("Galactic Record")),
+        record_area,
+    );
+
+    /* Newest lines at the bottom, unless scrolled back */
+    let height = log_area.height.saturating_sub(2) as usize;
+    let end = log.len().saturating_sub(scroll);
+    let start = end.saturating_sub(height);
+
+    let title = if scroll > 0 {
+        "Log (PageDown for newer)"
+    } else {
+        "Log"
+    };
+
+    frame.render_widget(
+        Paragraph::new(
+            log[start..end]
+                .iter()
+                .map(|l| Line::from(l.as_str()))
+                .collect::<Vec<_>>(),
+        )
+        .block(Block::bordered().title(title)),
+        log_area,
+    );
+
+    let text = format!("{prompt} {input}");
+
+    frame.render_widget(
+        Paragraph::new(text.as_str()).block(Block::bordered()),
+        prompt_area,
+    );
+    frame.set_cursor_position(Position::new(
+        prompt_area.x + 1 + text.chars().count() as u16,
+        prompt_area.y + 1,
+    ));
+}